@@ -1,3 +1,5 @@
+use std::fmt;
+
 use super::location::Location;
 
 #[derive(Debug)]
@@ -5,6 +7,9 @@ pub enum LitKind {
     Int(u64),
     Float(f64),
     Bool(bool),
+    Str(String),
+    /// Char literal, holding the ASCII value of the character.
+    Char(u8),
 }
 
 #[derive(Debug)]
@@ -19,9 +24,18 @@ pub struct Ident {
     pub location: Location,
 }
 
+#[derive(Debug)]
+pub enum TyKind {
+    Named(String),
+    /// Fixed-size array `[T; N]`.
+    Array(Box<Ty>, u64),
+    /// Slice `[T]`, a view into the elements of an array.
+    Slice(Box<Ty>),
+}
+
 #[derive(Debug)]
 pub struct Ty {
-    pub name: String,
+    pub kind: TyKind,
     pub location: Location,
 }
 
@@ -31,31 +45,84 @@ pub enum LogicalOp {
     Or,
 }
 
+impl fmt::Display for LogicalOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            LogicalOp::And => "&&",
+            LogicalOp::Or => "||",
+        };
+
+        write!(f, "{}", symbol)
+    }
+}
+
 #[derive(Debug)]
 pub enum BinaryOp {
     Add,
     Subtract,
     Multiply,
     Divide,
+    Equal,
+    NotEqual,
     Greater,
     GreaterEqual,
     Less,
     LessEqual,
 }
 
+impl fmt::Display for BinaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Subtract => "-",
+            BinaryOp::Multiply => "*",
+            BinaryOp::Divide => "/",
+            BinaryOp::Equal => "==",
+            BinaryOp::NotEqual => "!=",
+            BinaryOp::Greater => ">",
+            BinaryOp::GreaterEqual => ">=",
+            BinaryOp::Less => "<",
+            BinaryOp::LessEqual => "<=",
+        };
+
+        write!(f, "{}", symbol)
+    }
+}
+
 #[derive(Debug)]
 pub enum UnaryOp {
     Not,
     Minus,
 }
 
+impl fmt::Display for UnaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            UnaryOp::Not => "!",
+            UnaryOp::Minus => "-",
+        };
+
+        write!(f, "{}", symbol)
+    }
+}
+
 #[derive(Debug)]
 pub enum ExprKind {
+    Assign(Box<Expr>, Box<Expr>),
+    AssignOp(BinaryOp, Box<Expr>, Box<Expr>),
     Logical(LogicalOp, Box<Expr>, Box<Expr>),
     Binary(BinaryOp, Box<Expr>, Box<Expr>),
     Unary(UnaryOp, Box<Expr>),
+    /// Conditional expression, holding the condition and the values of both branches.
+    If(Box<Expr>, Box<Expr>, Box<Expr>),
     Ident(Ident),
     Call(Box<Expr>, Vec<Box<Expr>>),
+    Field(Box<Expr>, Ident),
+    Index(Box<Expr>, Box<Expr>),
+    /// Slice of an array or of another slice, between optional start and end indexes.
+    Slice(Box<Expr>, Option<Box<Expr>>, Option<Box<Expr>>),
+    Struct(Ident, Vec<(Ident, Box<Expr>)>),
+    Array(Vec<Box<Expr>>),
     Lit(Lit),
 }
 
@@ -65,6 +132,23 @@ pub struct Expr {
     pub location: Location,
 }
 
+impl Expr {
+    /// Returns true if the expression designates a memory location that can be assigned to.
+    pub fn is_place(&self) -> bool {
+        self.non_place().is_none()
+    }
+
+    /// Returns the sub-expression preventing the expression from being a place: the expression
+    /// itself, or the innermost base of a field or index expression that isn't a place.
+    pub fn non_place(&self) -> Option<&Expr> {
+        match &self.kind {
+            ExprKind::Ident(_) => None,
+            ExprKind::Field(base, _) | ExprKind::Index(base, _) => base.non_place(),
+            _ => Some(self),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Block {
     pub stmts: Vec<Stmt>,
@@ -75,6 +159,11 @@ pub struct Block {
 pub enum StmtKind {
     Decl(Decl),
     Ret(Option<Expr>),
+    If(Expr, Block, Option<Box<Stmt>>),
+    While(Option<Ident>, Expr, Block),
+    Loop(Option<Ident>, Block),
+    Break(Option<Ident>),
+    Continue(Option<Ident>),
     Block(Block),
     Expr(Expr),
 }
@@ -98,7 +187,8 @@ pub struct Decl {
 
 #[derive(Debug)]
 pub enum TopLevelDeclKind {
-    Fn(Ident, Vec<(Ident, Ty)>, Ty, Block),
+    Fn(Ident, Vec<(Ident, Ty)>, Option<Ty>, Block),
+    Struct(Ident, Vec<(Ident, Ty)>),
 }
 
 #[derive(Debug)]
@@ -1,12 +1,17 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Identifier of a file registered in a `SourceMap`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileId(usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Location {
+    pub file: FileId,
     pub start: usize,
     pub end: usize,
 }
 
 impl Location {
-    pub fn new(start: usize, end: usize) -> Self {
-        Location { start, end }
+    pub fn new(file: FileId, start: usize, end: usize) -> Self {
+        Location { file, start, end }
     }
 
     pub fn start(&self) -> usize {
@@ -16,4 +21,142 @@ impl Location {
     pub fn end(&self) -> usize {
         self.end
     }
+
+    /// Returns the location spanning from the start of this location to the end of `other`.
+    pub fn to(&self, other: Location) -> Location {
+        Location::new(self.file, self.start, other.end)
+    }
+}
+
+/// Zero-based line and column of a position in a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineCol {
+    pub line: usize,
+    pub col: usize,
+}
+
+#[derive(Debug)]
+pub struct SourceFile {
+    id: FileId,
+    name: String,
+    src: String,
+    /// Byte offset of the start of each line.
+    line_starts: Vec<usize>,
+}
+
+impl SourceFile {
+    fn new(id: FileId, name: String, src: String) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(src.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+
+        SourceFile {
+            id,
+            name,
+            src,
+            line_starts,
+        }
+    }
+
+    pub fn id(&self) -> FileId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn src(&self) -> &str {
+        &self.src
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Returns the zero-based index of the line containing the byte offset.
+    pub fn line_index(&self, offset: usize) -> usize {
+        match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next_line) => next_line - 1,
+        }
+    }
+
+    /// Returns the byte range of a line, excluding the line terminator.
+    pub fn line_range(&self, line: usize) -> (usize, usize) {
+        let start = self.line_starts[line];
+        let end = match self.line_starts.get(line + 1) {
+            Some(next_start) => next_start - 1,
+            None => self.src.len(),
+        };
+
+        let end = if self.src[start..end].ends_with('\r') {
+            end - 1
+        } else {
+            end
+        };
+
+        (start, end)
+    }
+
+    /// Returns the line and column of a byte offset, the column being counted in bytes.
+    pub fn line_col(&self, offset: usize) -> LineCol {
+        let line = self.line_index(offset);
+
+        LineCol {
+            line,
+            col: offset - self.line_starts[line],
+        }
+    }
+
+    /// Returns the line and column of a byte offset, the column being counted in UTF-16 code
+    /// units as expected by editors implementing the language server protocol.
+    pub fn line_col_utf16(&self, offset: usize) -> LineCol {
+        let line = self.line_index(offset);
+        let line_start = self.line_starts[line];
+
+        LineCol {
+            line,
+            col: self.src[line_start..offset].encode_utf16().count(),
+        }
+    }
+}
+
+/// Collection of the source files of a program. Files are addressed by the `FileId` stored in
+/// each `Location`.
+#[derive(Debug, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        SourceMap::default()
+    }
+
+    pub fn add_file(&mut self, name: String, src: String) -> FileId {
+        let id = FileId(self.files.len());
+        self.files.push(SourceFile::new(id, name, src));
+        id
+    }
+
+    pub fn file(&self, id: FileId) -> &SourceFile {
+        &self.files[id.0]
+    }
+
+    /// Returns the line and column of the start and the end of a location, counted in bytes.
+    pub fn line_col(&self, location: Location) -> (LineCol, LineCol) {
+        let file = self.file(location.file);
+        (file.line_col(location.start), file.line_col(location.end))
+    }
+
+    /// Returns the line and column of the start and the end of a location, counted in UTF-16
+    /// code units.
+    pub fn line_col_utf16(&self, location: Location) -> (LineCol, LineCol) {
+        let file = self.file(location.file);
+        (
+            file.line_col_utf16(location.start),
+            file.line_col_utf16(location.end),
+        )
+    }
 }
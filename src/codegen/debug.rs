@@ -0,0 +1,475 @@
+use std::{cell::RefCell, collections::HashMap, path::Path};
+
+use inkwell::{
+    basic_block::BasicBlock,
+    builder::Builder,
+    context::Context,
+    debug_info::{
+        AsDIScope, DICompileUnit, DIFile, DIFlags, DIFlagsConstants, DIScope, DIType,
+        DWARFEmissionKind, DWARFSourceLanguage, DebugInfoBuilder,
+    },
+    module::{FlagBehavior, Module},
+    targets::TargetData,
+    types::{BasicType, BasicTypeEnum, StructType},
+    values::{FunctionValue, PointerValue},
+    AddressSpace,
+};
+
+use crate::{
+    ast::location::{LineCol, Location, SourceFile},
+    sema::{
+        ty::{FloatTy, IntTy, Type, UIntTy},
+        TypeTable,
+    },
+};
+
+// DWARF attribute encodings of the base types, from the DWARF 4 specification.
+const DW_ATE_BOOLEAN: u32 = 0x02;
+const DW_ATE_FLOAT: u32 = 0x04;
+const DW_ATE_SIGNED: u32 = 0x05;
+const DW_ATE_UNSIGNED: u32 = 0x07;
+
+/// Debug information attached to the module when compiling with `-g`, describing the program
+/// in DWARF so that debuggers can map the generated code back to the source file.
+pub struct DebugInfo<'ctx> {
+    context: &'ctx Context,
+    file: &'ctx SourceFile,
+    types: &'ctx TypeTable,
+    builder: DebugInfoBuilder<'ctx>,
+    compile_unit: DICompileUnit<'ctx>,
+    /// Data layout of the module, giving the size, alignment and offsets of the types.
+    target_data: TargetData,
+    /// Types already described, so that each one is only described once in the module.
+    di_types: RefCell<HashMap<Type, DIType<'ctx>>>,
+    /// Lexical scopes enclosing the code being generated, the innermost one being last.
+    scopes: Vec<DIScope<'ctx>>,
+}
+
+impl<'ctx> DebugInfo<'ctx> {
+    /// Create the debug information of a module, whose target must already be configured so
+    /// that the types are described with its data layout.
+    pub fn new(
+        context: &'ctx Context,
+        module: &Module<'ctx>,
+        file: &'ctx SourceFile,
+        types: &'ctx TypeTable,
+    ) -> Self {
+        let path = Path::new(file.name());
+        let filename = path
+            .file_name()
+            .map_or_else(|| file.name().into(), |name| name.to_string_lossy());
+        let directory = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_string_lossy(),
+            _ => ".".into(),
+        };
+
+        // Debuggers ignore debug information that doesn't declare the version of its format.
+        module.add_basic_value_flag(
+            "Debug Info Version",
+            FlagBehavior::Warning,
+            context.i32_type().const_int(3, false),
+        );
+        module.add_basic_value_flag(
+            "Dwarf Version",
+            FlagBehavior::Warning,
+            context.i32_type().const_int(4, false),
+        );
+
+        let (builder, compile_unit) = module.create_debug_info_builder(
+            true,
+            DWARFSourceLanguage::C,
+            &filename,
+            &directory,
+            concat!("turbo-bear ", env!("CARGO_PKG_VERSION")),
+            false,
+            "",
+            0,
+            "",
+            DWARFEmissionKind::Full,
+            0,
+            false,
+            false,
+            "",
+            "",
+        );
+
+        let target_data = TargetData::create(&module.get_data_layout().as_str().to_string_lossy());
+
+        DebugInfo {
+            context,
+            file,
+            types,
+            builder,
+            compile_unit,
+            target_data,
+            di_types: RefCell::new(HashMap::new()),
+            scopes: Vec::new(),
+        }
+    }
+
+    fn di_file(&self) -> DIFile<'ctx> {
+        self.compile_unit.get_file()
+    }
+
+    /// Returns the one-based line and column of a byte offset, as expected by DWARF.
+    fn line_col(&self, offset: usize) -> (u32, u32) {
+        let LineCol { line, col } = self.file.line_col(offset);
+        (line as u32 + 1, col as u32 + 1)
+    }
+
+    fn scope(&self) -> DIScope<'ctx> {
+        *self
+            .scopes
+            .last()
+            .expect("Debug scope requested outside of a function")
+    }
+
+    fn di_type(&self, ty: Type) -> DIType<'ctx> {
+        if let Some(di_type) = self.di_types.borrow().get(&ty) {
+            return *di_type;
+        }
+
+        let di_type = match ty {
+            Type::Str => self.di_slice_type(ty, Type::UInt(UIntTy::U8)),
+            Type::Slice(element) => self.di_slice_type(ty, *element),
+            Type::Struct(name) => self.di_struct_type(name),
+            Type::Array((element, len)) => self.di_array_type(*element, *len),
+            _ => {
+                let (name, size_in_bits, encoding) = basic_type_info(ty);
+
+                self.builder
+                    .create_basic_type(name, size_in_bits, encoding, DIFlags::PUBLIC)
+                    .expect("Invalid debug type")
+                    .as_type()
+            }
+        };
+
+        self.di_types.borrow_mut().insert(ty, di_type);
+        di_type
+    }
+
+    fn di_struct_type(&self, name: &str) -> DIType<'ctx> {
+        let def = self.types.struct_def(name);
+        let (line, _) = self.line_col(def.location.start);
+        let scope = self.compile_unit.as_debug_info_scope();
+        let struct_type = self.llvm_type(Type::struct_named(name)).into_struct_type();
+
+        let members = def
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(index, (field_name, field_ty))| {
+                let (size_in_bits, align_in_bits) = self.layout(*field_ty);
+
+                self.builder
+                    .create_member_type(
+                        scope,
+                        field_name,
+                        self.di_file(),
+                        line,
+                        size_in_bits,
+                        align_in_bits,
+                        self.offset_in_bits(struct_type, index),
+                        DIFlags::PUBLIC,
+                        self.di_type(*field_ty),
+                    )
+                    .as_type()
+            })
+            .collect::<Vec<_>>();
+
+        let (size_in_bits, align_in_bits) = self.layout(Type::struct_named(name));
+
+        self.builder
+            .create_struct_type(
+                scope,
+                name,
+                self.di_file(),
+                line,
+                size_in_bits,
+                align_in_bits,
+                DIFlags::PUBLIC,
+                None,
+                &members,
+                0,
+                None,
+                name,
+            )
+            .as_type()
+    }
+
+    /// Strings and slices are described as a struct holding a pointer to their first element and
+    /// their length.
+    fn di_slice_type(&self, ty: Type, element: Type) -> DIType<'ctx> {
+        let scope = self.compile_unit.as_debug_info_scope();
+        let name = ty.to_string();
+        let slice_type = self.llvm_type(ty).into_struct_type();
+
+        let ptr_layout = self.pointer_layout(element);
+        let ptr_type = self
+            .builder
+            .create_pointer_type(
+                &format!("*{}", element),
+                self.di_type(element),
+                ptr_layout.0,
+                ptr_layout.1,
+                AddressSpace::Generic,
+            )
+            .as_type();
+        let len_layout = self.layout(Type::UInt(UIntTy::U64));
+        let len_type = self.di_type(Type::UInt(UIntTy::U64));
+
+        let members = [("ptr", ptr_type, ptr_layout), ("len", len_type, len_layout)]
+            .iter()
+            .enumerate()
+            .map(|(index, (name, ty, (size_in_bits, align_in_bits)))| {
+                self.builder
+                    .create_member_type(
+                        scope,
+                        name,
+                        self.di_file(),
+                        0,
+                        *size_in_bits,
+                        *align_in_bits,
+                        self.offset_in_bits(slice_type, index),
+                        DIFlags::PUBLIC,
+                        *ty,
+                    )
+                    .as_type()
+            })
+            .collect::<Vec<_>>();
+
+        let (size_in_bits, align_in_bits) = self.layout(ty);
+
+        self.builder
+            .create_struct_type(
+                scope,
+                &name,
+                self.di_file(),
+                0,
+                size_in_bits,
+                align_in_bits,
+                DIFlags::PUBLIC,
+                None,
+                &members,
+                0,
+                None,
+                &name,
+            )
+            .as_type()
+    }
+
+    fn di_array_type(&self, element: Type, len: u64) -> DIType<'ctx> {
+        let (size_in_bits, align_in_bits) = self.layout(Type::array_of(element, len));
+
+        self.builder
+            .create_array_type(
+                self.di_type(element),
+                size_in_bits,
+                align_in_bits,
+                std::slice::from_ref(&(0..len as i64)),
+            )
+            .as_type()
+    }
+
+    /// Returns the LLVM type laid out like the values of `ty` in the generated code, to query
+    /// the data layout. Structs are built as literal structs with the same fields as the named
+    /// ones, which have the same layout.
+    fn llvm_type(&self, ty: Type) -> BasicTypeEnum<'ctx> {
+        match ty {
+            Type::Void => unreachable!("'void' has no value"),
+            Type::Bool => self.context.bool_type().into(),
+            Type::Int(IntTy::I8) | Type::UInt(UIntTy::U8) => self.context.i8_type().into(),
+            Type::Int(IntTy::I16) | Type::UInt(UIntTy::U16) => self.context.i16_type().into(),
+            Type::Int(IntTy::I32) | Type::UInt(UIntTy::U32) => self.context.i32_type().into(),
+            Type::Int(IntTy::I64) | Type::UInt(UIntTy::U64) => self.context.i64_type().into(),
+            Type::Float(FloatTy::F32) => self.context.f32_type().into(),
+            Type::Float(FloatTy::F64) => self.context.f64_type().into(),
+            Type::Str => self.llvm_type(Type::slice_of(Type::UInt(UIntTy::U8))),
+            Type::Slice(element) => {
+                let ptr_type = self.llvm_type(*element).ptr_type(AddressSpace::Generic);
+                let len_type = self.context.i64_type();
+                self.context
+                    .struct_type(&[ptr_type.into(), len_type.into()], false)
+                    .into()
+            }
+            Type::Struct(name) => {
+                let fields = self
+                    .types
+                    .struct_def(name)
+                    .fields
+                    .iter()
+                    .map(|(_, ty)| self.llvm_type(*ty))
+                    .collect::<Vec<_>>();
+                self.context.struct_type(&fields, false).into()
+            }
+            Type::Array((element, len)) => self.llvm_type(*element).array_type(*len as u32).into(),
+        }
+    }
+
+    /// Returns the size and the alignment of a type in bits, from the data layout of the module.
+    fn layout(&self, ty: Type) -> (u64, u32) {
+        let llvm_type = self.llvm_type(ty);
+
+        (
+            self.target_data.get_abi_size(&llvm_type) * 8,
+            self.target_data.get_abi_alignment(&llvm_type) * 8,
+        )
+    }
+
+    /// Returns the size and the alignment in bits of a pointer to `element`.
+    fn pointer_layout(&self, element: Type) -> (u64, u32) {
+        let ptr_type = self.llvm_type(element).ptr_type(AddressSpace::Generic);
+
+        (
+            self.target_data.get_bit_size(&ptr_type),
+            self.target_data.get_abi_alignment(&ptr_type) * 8,
+        )
+    }
+
+    fn offset_in_bits(&self, struct_type: StructType<'ctx>, index: usize) -> u64 {
+        self.target_data
+            .offset_of_element(&struct_type, index as u32)
+            .expect("Invalid field index")
+            * 8
+    }
+
+    /// Attach a subprogram to a function and enter its scope. The scope is left with
+    /// `exit_scope` once the function body is built.
+    pub fn enter_fn(
+        &mut self,
+        fn_value: FunctionValue<'ctx>,
+        name: &str,
+        params: &[Type],
+        ret: Type,
+        location: Location,
+        body_location: Location,
+    ) {
+        let param_types = params
+            .iter()
+            .map(|ty| self.di_type(*ty))
+            .collect::<Vec<_>>();
+        let return_type = match ret {
+            Type::Void => None,
+            ret => Some(self.di_type(ret)),
+        };
+        let subroutine_type = self.builder.create_subroutine_type(
+            self.di_file(),
+            return_type,
+            &param_types,
+            DIFlags::PUBLIC,
+        );
+
+        let (line, _) = self.line_col(location.start);
+        let (scope_line, _) = self.line_col(body_location.start);
+
+        let subprogram = self.builder.create_function(
+            self.compile_unit.as_debug_info_scope(),
+            name,
+            None,
+            self.di_file(),
+            line,
+            subroutine_type,
+            false,
+            true,
+            scope_line,
+            DIFlags::PUBLIC,
+            false,
+        );
+
+        fn_value.set_subprogram(subprogram);
+        self.scopes.push(subprogram.as_debug_info_scope());
+    }
+
+    /// Enter the lexical scope of a block.
+    pub fn enter_block(&mut self, location: Location) {
+        let (line, col) = self.line_col(location.start);
+        let block = self
+            .builder
+            .create_lexical_block(self.scope(), self.di_file(), line, col);
+
+        self.scopes.push(block.as_debug_info_scope());
+    }
+
+    pub fn exit_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    /// Set the source location attached to the instructions created by the builder from now
+    /// on.
+    pub fn set_location(&self, builder: &Builder<'ctx>, location: Location) {
+        let (line, col) = self.line_col(location.start);
+        let debug_location =
+            self.builder
+                .create_debug_location(self.context, line, col, self.scope(), None);
+
+        builder.set_current_debug_location(self.context, debug_location);
+    }
+
+    /// Describe a local variable stored in `ptr`. Parameters are numbered from 1 with `arg_no`.
+    pub fn declare_var(
+        &self,
+        ptr: PointerValue<'ctx>,
+        name: &str,
+        ty: Type,
+        arg_no: Option<u32>,
+        location: Location,
+        block: BasicBlock<'ctx>,
+    ) {
+        let (line, col) = self.line_col(location.start);
+        let di_type = self.di_type(ty);
+
+        let variable = match arg_no {
+            Some(arg_no) => self.builder.create_parameter_variable(
+                self.scope(),
+                name,
+                arg_no,
+                self.di_file(),
+                line,
+                di_type,
+                true,
+                DIFlags::ZERO,
+            ),
+            None => self.builder.create_auto_variable(
+                self.scope(),
+                name,
+                self.di_file(),
+                line,
+                di_type,
+                true,
+                DIFlags::ZERO,
+                0,
+            ),
+        };
+
+        let debug_location =
+            self.builder
+                .create_debug_location(self.context, line, col, self.scope(), None);
+        self.builder
+            .insert_declare_at_end(ptr, Some(variable), None, debug_location, block);
+    }
+
+    /// Resolve the debug information, which must be done before the module is verified.
+    pub fn finalize(&self) {
+        self.builder.finalize();
+    }
+}
+
+/// Returns the name, the size in bits and the DWARF encoding of a primitive type.
+fn basic_type_info(ty: Type) -> (&'static str, u64, u32) {
+    match ty {
+        Type::Bool => ("bool", 8, DW_ATE_BOOLEAN),
+        Type::Int(IntTy::I8) => ("i8", 8, DW_ATE_SIGNED),
+        Type::Int(IntTy::I16) => ("i16", 16, DW_ATE_SIGNED),
+        Type::Int(IntTy::I32) => ("i32", 32, DW_ATE_SIGNED),
+        Type::Int(IntTy::I64) => ("i64", 64, DW_ATE_SIGNED),
+        Type::UInt(UIntTy::U8) => ("u8", 8, DW_ATE_UNSIGNED),
+        Type::UInt(UIntTy::U16) => ("u16", 16, DW_ATE_UNSIGNED),
+        Type::UInt(UIntTy::U32) => ("u32", 32, DW_ATE_UNSIGNED),
+        Type::UInt(UIntTy::U64) => ("u64", 64, DW_ATE_UNSIGNED),
+        Type::Float(FloatTy::F32) => ("f32", 32, DW_ATE_FLOAT),
+        Type::Float(FloatTy::F64) => ("f64", 64, DW_ATE_FLOAT),
+        Type::Void | Type::Str | Type::Struct(_) | Type::Array(_) | Type::Slice(_) => {
+            unreachable!("'{}' is not a primitive type", ty)
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use std::fmt;
+
+use crate::ast::location::Location;
+
+#[derive(Debug)]
+pub enum CodegenErrorKind {
+    InvalidModule(String),
+}
+
+impl fmt::Display for CodegenErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use CodegenErrorKind::*;
+
+        match self {
+            InvalidModule(msg) => {
+                write!(f, "Invalid LLVM module. {}", msg)
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct CodegenError {
+    pub kind: CodegenErrorKind,
+    pub location: Location,
+}
+
+impl CodegenError {
+    pub fn new(kind: CodegenErrorKind, location: Location) -> Self {
+        CodegenError { kind, location }
+    }
+}
+
+impl fmt::Display for CodegenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind)
+    }
+}
@@ -0,0 +1,30 @@
+use inkwell::{
+    module::Module,
+    targets::{InitializationConfig, Target},
+    OptimizationLevel,
+};
+
+use super::target;
+
+type MainFn = unsafe extern "C" fn() -> i32;
+
+/// Compile the module in memory for the host and execute its `main` function, returning the
+/// value it produces.
+pub fn run_main(module: &Module, level: OptimizationLevel) -> Result<i32, String> {
+    target::check_entry_point(module)?;
+
+    Target::initialize_native(&InitializationConfig::default())?;
+
+    let engine = module
+        .create_jit_execution_engine(level)
+        .map_err(|err| err.to_string())?;
+
+    // Safety: the signature of `main` is checked above to match `MainFn`.
+    unsafe {
+        let main = engine
+            .get_function::<MainFn>("main")
+            .map_err(|err| err.to_string())?;
+
+        Ok(main.call())
+    }
+}
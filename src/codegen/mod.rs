@@ -0,0 +1,1456 @@
+use std::collections::HashMap;
+
+use inkwell::{
+    basic_block::BasicBlock,
+    builder::Builder,
+    context::Context,
+    module::{Linkage, Module},
+    targets::TargetMachine,
+    types::{BasicType, BasicTypeEnum, FunctionType, StructType},
+    values::{BasicValue, BasicValueEnum, FunctionValue, IntValue, PointerValue, StructValue},
+    AddressSpace, FloatPredicate, IntPredicate,
+};
+
+use crate::{
+    ast::{
+        ast::*,
+        location::{LineCol, Location, SourceFile},
+    },
+    sema::{
+        ty::{FloatTy, IntTy, Type, UIntTy},
+        TypeTable, RUNTIME_FUNCTIONS,
+    },
+};
+
+pub mod debug;
+pub mod error;
+pub mod jit;
+pub mod optimize;
+pub mod target;
+
+use self::{
+    debug::DebugInfo,
+    error::{CodegenError, CodegenErrorKind},
+};
+
+/// Options controlling the generated code.
+#[derive(Debug, Clone, Copy)]
+pub struct CodegenOptions {
+    /// Emit DWARF debug information.
+    pub debug_info: bool,
+    /// Abort the program when an array is indexed out of its bounds.
+    pub bounds_checks: bool,
+}
+
+impl Default for CodegenOptions {
+    fn default() -> Self {
+        CodegenOptions {
+            debug_info: false,
+            bounds_checks: true,
+        }
+    }
+}
+
+/// Branch targets of a loop being built, used to lower `break` and `continue`.
+struct LoopTarget<'ctx> {
+    label: Option<String>,
+    continue_bb: BasicBlock<'ctx>,
+    break_bb: Option<BasicBlock<'ctx>>,
+}
+
+struct CodeGen<'ctx> {
+    file: &'ctx SourceFile,
+    program: &'ctx Program,
+    types: &'ctx TypeTable,
+    context: &'ctx Context,
+    builder: Builder<'ctx>,
+    module: Module<'ctx>,
+    scopes: Vec<HashMap<String, PointerValue<'ctx>>>,
+    loops: Vec<LoopTarget<'ctx>>,
+    bounds_checks: bool,
+    debug_info: Option<DebugInfo<'ctx>>,
+    /// Location of the source code being lowered, attached to the instructions when emitting
+    /// debug information.
+    debug_location: Option<Location>,
+}
+
+impl<'ctx> CodeGen<'ctx> {
+    fn new(
+        name: &str,
+        file: &'ctx SourceFile,
+        program: &'ctx Program,
+        types: &'ctx TypeTable,
+        context: &'ctx Context,
+        machine: Option<&TargetMachine>,
+        options: CodegenOptions,
+    ) -> Self {
+        let builder = context.create_builder();
+        let module = context.create_module(name);
+        // The debug information describes the types with the data layout of the target.
+        if let Some(machine) = machine {
+            target::configure_module(&module, machine);
+        }
+        let debug_info = if options.debug_info {
+            Some(DebugInfo::new(context, &module, file, types))
+        } else {
+            None
+        };
+
+        CodeGen {
+            file,
+            program,
+            types,
+            context,
+            builder,
+            module,
+            scopes: Vec::new(),
+            loops: Vec::new(),
+            bounds_checks: options.bounds_checks,
+            debug_info,
+            debug_location: None,
+        }
+    }
+
+    /// Attach the location to the instructions built from now on, returning the previous
+    /// location so that it can be restored.
+    fn set_debug_location(&mut self, location: Location) -> Option<Location> {
+        if let Some(debug_info) = &self.debug_info {
+            debug_info.set_location(&self.builder, location);
+        }
+
+        self.debug_location.replace(location)
+    }
+
+    fn get_type(&self, ty: Type) -> BasicTypeEnum<'ctx> {
+        match ty {
+            Type::Void => unreachable!("'void' has no value"),
+            Type::Bool => self.context.bool_type().into(),
+            Type::Int(IntTy::I8) | Type::UInt(UIntTy::U8) => self.context.i8_type().into(),
+            Type::Int(IntTy::I16) | Type::UInt(UIntTy::U16) => self.context.i16_type().into(),
+            Type::Int(IntTy::I32) | Type::UInt(UIntTy::U32) => self.context.i32_type().into(),
+            Type::Int(IntTy::I64) | Type::UInt(UIntTy::U64) => self.context.i64_type().into(),
+            Type::Float(FloatTy::F32) => self.context.f32_type().into(),
+            Type::Float(FloatTy::F64) => self.context.f64_type().into(),
+            // Strings and slices are a pointer to their first element followed by their length.
+            Type::Str => self.get_slice_type(Type::UInt(UIntTy::U8)).into(),
+            Type::Slice(element) => self.get_slice_type(*element).into(),
+            Type::Struct(name) => self
+                .module
+                .get_struct_type(name)
+                .expect("Struct used before being declared")
+                .into(),
+            Type::Array((element, len)) => self.get_type(*element).array_type(*len as u32).into(),
+        }
+    }
+
+    fn get_slice_type(&self, element: Type) -> StructType<'ctx> {
+        let ptr_type = self.get_type(element).ptr_type(AddressSpace::Generic);
+        let len_type = self.context.i64_type();
+        self.context
+            .struct_type(&[ptr_type.into(), len_type.into()], false)
+    }
+
+    fn get_function(&self, name: &str) -> Option<FunctionValue<'ctx>> {
+        self.module.get_function(name)
+    }
+
+    /// Returns a function of the C library called by the generated code, declaring it on first
+    /// use. The program can't declare functions with the same name.
+    fn get_libc_function(&self, name: &str, fn_type: FunctionType<'ctx>) -> FunctionValue<'ctx> {
+        debug_assert!(RUNTIME_FUNCTIONS.contains(&name));
+
+        self.get_function(name).unwrap_or_else(|| {
+            self.module
+                .add_function(name, fn_type, Some(Linkage::External))
+        })
+    }
+
+    /// Returns the `write` function, used to write to a file descriptor without going through
+    /// the buffered streams of the C library, which aren't flushed on `abort`.
+    fn get_write_function(&self) -> FunctionValue<'ctx> {
+        let i8_ptr_type = self.context.i8_type().ptr_type(AddressSpace::Generic);
+        let i32_type = self.context.i32_type();
+        let i64_type = self.context.i64_type();
+
+        self.get_libc_function(
+            "write",
+            i64_type.fn_type(
+                &[i32_type.into(), i8_ptr_type.into(), i64_type.into()],
+                false,
+            ),
+        )
+    }
+
+    /// Returns the pointer to the first element and the length of a slice or a string, whose
+    /// elements are its bytes.
+    fn build_slice_parts(&self, value: StructValue<'ctx>) -> (PointerValue<'ctx>, IntValue<'ctx>) {
+        let ptr = self
+            .builder
+            .build_extract_value(value, 0, "sliceptr")
+            .expect("Invalid slice value")
+            .into_pointer_value();
+        let len = self
+            .builder
+            .build_extract_value(value, 1, "slicelen")
+            .expect("Invalid slice value")
+            .into_int_value();
+
+        (ptr, len)
+    }
+
+    fn enter_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    fn exit_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn declare_var(&mut self, name: &str, ptr: PointerValue<'ctx>) {
+        self.scopes
+            .last_mut()
+            .expect("Variable declared outside of a scope")
+            .insert(name.to_owned(), ptr);
+    }
+
+    /// Resolve a variable by walking the scopes from the innermost to the outermost one, so
+    /// that declarations in nested blocks shadow the ones from the enclosing blocks. The semantic
+    /// analysis ensures that the variable is declared.
+    fn lookup_var(&self, ident: &Ident) -> PointerValue<'ctx> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(&ident.name).copied())
+            .unwrap_or_else(|| unreachable!("Unknown variable {}", ident.name))
+    }
+
+    fn current_fn(&self) -> FunctionValue<'ctx> {
+        self.builder
+            .get_insert_block()
+            .and_then(|block| block.get_parent())
+            .expect("Builder positioned outside of a function")
+    }
+
+    /// Allocate a stack slot in the entry block of the current function. Keeping all the
+    /// allocas in the entry block allows LLVM to promote them to registers.
+    fn build_entry_alloca<T: BasicType<'ctx>>(&self, ty: T, name: &str) -> PointerValue<'ctx> {
+        let entry = self.current_fn().get_first_basic_block().unwrap();
+
+        let builder = self.context.create_builder();
+        match entry.get_first_instruction() {
+            Some(instruction) => builder.position_before(&instruction),
+            None => builder.position_at_end(entry),
+        }
+
+        builder.build_alloca(ty, name)
+    }
+
+    fn build_logical(
+        &mut self,
+        op: &LogicalOp,
+        left: &Expr,
+        right: &Expr,
+    ) -> Result<BasicValueEnum<'ctx>, CodegenError> {
+        let lhs = self.build_expr(left)?.into_int_value();
+        let lhs_bb = self.builder.get_insert_block().unwrap();
+
+        let fn_value = self.current_fn();
+        let rhs_bb = self.context.append_basic_block(fn_value, "logical.rhs");
+        let end_bb = self.context.append_basic_block(fn_value, "logical.end");
+
+        // The right operand is only evaluated when the left operand doesn't already determine
+        // the result: `false && ..` is always false and `true || ..` is always true.
+        let short_circuit_value = match op {
+            LogicalOp::And => {
+                self.builder.build_conditional_branch(lhs, rhs_bb, end_bb);
+                self.context.bool_type().const_zero()
+            }
+            LogicalOp::Or => {
+                self.builder.build_conditional_branch(lhs, end_bb, rhs_bb);
+                self.context.bool_type().const_all_ones()
+            }
+        };
+
+        self.builder.position_at_end(rhs_bb);
+        let rhs = self.build_expr(right)?.into_int_value();
+        // Evaluating the right operand may have moved the builder to a different block.
+        let rhs_bb = self.builder.get_insert_block().unwrap();
+        self.builder.build_unconditional_branch(end_bb);
+
+        self.builder.position_at_end(end_bb);
+        let phi = self.builder.build_phi(self.context.bool_type(), "logical");
+        phi.add_incoming(&[(&short_circuit_value, lhs_bb), (&rhs, rhs_bb)]);
+
+        Ok(phi.as_basic_value())
+    }
+
+    fn build_binary(
+        &mut self,
+        op: &BinaryOp,
+        left: &Expr,
+        right: &Expr,
+    ) -> Result<BasicValueEnum<'ctx>, CodegenError> {
+        let lhs = self.build_expr(left)?;
+        let rhs = self.build_expr(right)?;
+
+        let ty = self.types.expr_type(left);
+        if ty == Type::Str {
+            let is_equal = self.build_str_equal(lhs.into_struct_value(), rhs.into_struct_value());
+
+            let value = match op {
+                BinaryOp::Equal => is_equal,
+                BinaryOp::NotEqual => self.builder.build_not(is_equal, "tmpnot"),
+                _ => unreachable!("Invalid string operation '{}'", op),
+            };
+
+            return Ok(value.into());
+        }
+
+        Ok(self.build_binary_op(op, ty, lhs, rhs))
+    }
+
+    /// Strings are equal when they have the same length and the same bytes.
+    fn build_str_equal(&self, lhs: StructValue<'ctx>, rhs: StructValue<'ctx>) -> IntValue<'ctx> {
+        let (lhs_ptr, lhs_len) = self.build_slice_parts(lhs);
+        let (rhs_ptr, rhs_len) = self.build_slice_parts(rhs);
+
+        let i64_type = self.context.i64_type();
+        let is_same_len =
+            self.builder
+                .build_int_compare(IntPredicate::EQ, lhs_len, rhs_len, "samelen");
+
+        // No bytes are compared when the lengths differ, which avoids branching on the lengths.
+        let cmp_len = self
+            .builder
+            .build_select(is_same_len, lhs_len, i64_type.const_zero(), "cmplen")
+            .into_int_value();
+
+        let i8_ptr_type = self.context.i8_type().ptr_type(AddressSpace::Generic);
+        let i32_type = self.context.i32_type();
+        let memcmp = self.get_libc_function(
+            "memcmp",
+            i32_type.fn_type(
+                &[i8_ptr_type.into(), i8_ptr_type.into(), i64_type.into()],
+                false,
+            ),
+        );
+
+        let cmp = self
+            .builder
+            .build_call(
+                memcmp,
+                &[lhs_ptr.into(), rhs_ptr.into(), cmp_len.into()],
+                "memcmp",
+            )
+            .try_as_basic_value()
+            .left()
+            .expect("memcmp returns a value")
+            .into_int_value();
+        let is_same_bytes = self.builder.build_int_compare(
+            IntPredicate::EQ,
+            cmp,
+            i32_type.const_zero(),
+            "samebytes",
+        );
+
+        self.builder.build_and(is_same_len, is_same_bytes, "streq")
+    }
+
+    /// Build a binary operation on two operands of type `ty`. The operand type picks between the
+    /// signed and unsigned variants of the integer instructions.
+    fn build_binary_op(
+        &self,
+        op: &BinaryOp,
+        ty: Type,
+        lhs: BasicValueEnum<'ctx>,
+        rhs: BasicValueEnum<'ctx>,
+    ) -> BasicValueEnum<'ctx> {
+        match op {
+            BinaryOp::Add => match (lhs, rhs) {
+                (BasicValueEnum::IntValue(lhs), BasicValueEnum::IntValue(rhs)) => {
+                    self.builder.build_int_add(lhs, rhs, "tmpadd").into()
+                }
+                (BasicValueEnum::FloatValue(lhs), BasicValueEnum::FloatValue(rhs)) => {
+                    self.builder.build_float_add(lhs, rhs, "tmpadd").into()
+                }
+                _ => panic!("Invalid add operation"),
+            },
+            BinaryOp::Subtract => match (lhs, rhs) {
+                (BasicValueEnum::IntValue(lhs), BasicValueEnum::IntValue(rhs)) => {
+                    self.builder.build_int_sub(lhs, rhs, "tmpsub").into()
+                }
+                (BasicValueEnum::FloatValue(lhs), BasicValueEnum::FloatValue(rhs)) => {
+                    self.builder.build_float_sub(lhs, rhs, "tmpsub").into()
+                }
+                _ => panic!("Invalid substract operation"),
+            },
+            BinaryOp::Multiply => match (lhs, rhs) {
+                (BasicValueEnum::IntValue(lhs), BasicValueEnum::IntValue(rhs)) => {
+                    self.builder.build_int_mul(lhs, rhs, "tmpmul").into()
+                }
+                (BasicValueEnum::FloatValue(lhs), BasicValueEnum::FloatValue(rhs)) => {
+                    self.builder.build_float_mul(lhs, rhs, "tmpmul").into()
+                }
+                _ => panic!("Invalid multiply operation"),
+            },
+            BinaryOp::Divide => match (lhs, rhs) {
+                (BasicValueEnum::IntValue(lhs), BasicValueEnum::IntValue(rhs)) => {
+                    if ty.is_signed() {
+                        self.builder.build_int_signed_div(lhs, rhs, "tmpdiv").into()
+                    } else {
+                        self.builder
+                            .build_int_unsigned_div(lhs, rhs, "tmpdiv")
+                            .into()
+                    }
+                }
+                (BasicValueEnum::FloatValue(lhs), BasicValueEnum::FloatValue(rhs)) => {
+                    self.builder.build_float_div(lhs, rhs, "tmpdiv").into()
+                }
+                _ => panic!("Invalid divide operation"),
+            },
+            // Booleans are lowered to `i1`, so they are compared as integers.
+            BinaryOp::Equal => match (lhs, rhs) {
+                (BasicValueEnum::IntValue(lhs), BasicValueEnum::IntValue(rhs)) => self
+                    .builder
+                    .build_int_compare(IntPredicate::EQ, lhs, rhs, "tmpcmp")
+                    .into(),
+                (BasicValueEnum::FloatValue(lhs), BasicValueEnum::FloatValue(rhs)) => self
+                    .builder
+                    .build_float_compare(FloatPredicate::OEQ, lhs, rhs, "tmpcmp")
+                    .into(),
+                _ => panic!("Invalid compare operation"),
+            },
+            BinaryOp::NotEqual => match (lhs, rhs) {
+                (BasicValueEnum::IntValue(lhs), BasicValueEnum::IntValue(rhs)) => self
+                    .builder
+                    .build_int_compare(IntPredicate::NE, lhs, rhs, "tmpcmp")
+                    .into(),
+                (BasicValueEnum::FloatValue(lhs), BasicValueEnum::FloatValue(rhs)) => self
+                    .builder
+                    .build_float_compare(FloatPredicate::UNE, lhs, rhs, "tmpcmp")
+                    .into(),
+                _ => panic!("Invalid compare operation"),
+            },
+            BinaryOp::Greater => match (lhs, rhs) {
+                (BasicValueEnum::IntValue(lhs), BasicValueEnum::IntValue(rhs)) => self
+                    .builder
+                    .build_int_compare(
+                        if ty.is_signed() {
+                            IntPredicate::SGT
+                        } else {
+                            IntPredicate::UGT
+                        },
+                        lhs,
+                        rhs,
+                        "tmpcmp",
+                    )
+                    .into(),
+                (BasicValueEnum::FloatValue(lhs), BasicValueEnum::FloatValue(rhs)) => self
+                    .builder
+                    .build_float_compare(FloatPredicate::OGT, lhs, rhs, "tmpcmp")
+                    .into(),
+                _ => panic!("Invalid compare operation"),
+            },
+            BinaryOp::GreaterEqual => match (lhs, rhs) {
+                (BasicValueEnum::IntValue(lhs), BasicValueEnum::IntValue(rhs)) => self
+                    .builder
+                    .build_int_compare(
+                        if ty.is_signed() {
+                            IntPredicate::SGE
+                        } else {
+                            IntPredicate::UGE
+                        },
+                        lhs,
+                        rhs,
+                        "tmpcmp",
+                    )
+                    .into(),
+                (BasicValueEnum::FloatValue(lhs), BasicValueEnum::FloatValue(rhs)) => self
+                    .builder
+                    .build_float_compare(FloatPredicate::OGE, lhs, rhs, "tmpcmp")
+                    .into(),
+                _ => panic!("Invalid compare operation"),
+            },
+            BinaryOp::Less => match (lhs, rhs) {
+                (BasicValueEnum::IntValue(lhs), BasicValueEnum::IntValue(rhs)) => self
+                    .builder
+                    .build_int_compare(
+                        if ty.is_signed() {
+                            IntPredicate::SLT
+                        } else {
+                            IntPredicate::ULT
+                        },
+                        lhs,
+                        rhs,
+                        "tmpcmp",
+                    )
+                    .into(),
+                (BasicValueEnum::FloatValue(lhs), BasicValueEnum::FloatValue(rhs)) => self
+                    .builder
+                    .build_float_compare(FloatPredicate::OLT, lhs, rhs, "tmpcmp")
+                    .into(),
+                _ => panic!("Invalid compare operation"),
+            },
+            BinaryOp::LessEqual => match (lhs, rhs) {
+                (BasicValueEnum::IntValue(lhs), BasicValueEnum::IntValue(rhs)) => self
+                    .builder
+                    .build_int_compare(
+                        if ty.is_signed() {
+                            IntPredicate::SLE
+                        } else {
+                            IntPredicate::ULE
+                        },
+                        lhs,
+                        rhs,
+                        "tmpcmp",
+                    )
+                    .into(),
+                (BasicValueEnum::FloatValue(lhs), BasicValueEnum::FloatValue(rhs)) => self
+                    .builder
+                    .build_float_compare(FloatPredicate::OLE, lhs, rhs, "tmpcmp")
+                    .into(),
+                _ => panic!("Invalid compare operation"),
+            },
+        }
+    }
+
+    fn build_unary(
+        &mut self,
+        op: &UnaryOp,
+        expr: &Expr,
+    ) -> Result<BasicValueEnum<'ctx>, CodegenError> {
+        let expr = self.build_expr(expr)?;
+
+        let value = match op {
+            UnaryOp::Not => match expr {
+                BasicValueEnum::IntValue(value) => self.builder.build_not(value, "tmpnot").into(),
+                _ => panic!("Invalid not operation"),
+            },
+            UnaryOp::Minus => match expr {
+                BasicValueEnum::IntValue(value) => {
+                    self.builder.build_int_neg(value, "tmpminus").into()
+                }
+                BasicValueEnum::FloatValue(value) => {
+                    self.builder.build_float_neg(value, "tmpminus").into()
+                }
+                _ => panic!("Invalid minus operation"),
+            },
+        };
+
+        Ok(value)
+    }
+
+    fn build_if_expr(
+        &mut self,
+        condition: &Expr,
+        then_expr: &Expr,
+        else_expr: &Expr,
+    ) -> Result<BasicValueEnum<'ctx>, CodegenError> {
+        let condition = self.build_expr(condition)?.into_int_value();
+
+        let fn_value = self.current_fn();
+        let then_bb = self.context.append_basic_block(fn_value, "if.then");
+        let else_bb = self.context.append_basic_block(fn_value, "if.else");
+        let end_bb = self.context.append_basic_block(fn_value, "if.end");
+        self.builder
+            .build_conditional_branch(condition, then_bb, else_bb);
+
+        // Evaluating a branch may move the builder to a different block, the incoming blocks of
+        // the phi are the ones where the branches end.
+        self.builder.position_at_end(then_bb);
+        let then_value = self.build_expr(then_expr)?;
+        let then_bb = self.builder.get_insert_block().unwrap();
+        self.builder.build_unconditional_branch(end_bb);
+
+        self.builder.position_at_end(else_bb);
+        let else_value = self.build_expr(else_expr)?;
+        let else_bb = self.builder.get_insert_block().unwrap();
+        self.builder.build_unconditional_branch(end_bb);
+
+        self.builder.position_at_end(end_bb);
+        let phi = self.builder.build_phi(then_value.get_type(), "if");
+        phi.add_incoming(&[(&then_value, then_bb), (&else_value, else_bb)]);
+
+        Ok(phi.as_basic_value())
+    }
+
+    fn build_call(
+        &mut self,
+        callee: &Expr,
+        args: &[Box<Expr>],
+    ) -> Result<BasicValueEnum<'ctx>, CodegenError> {
+        let ident = match &callee.kind {
+            ExprKind::Ident(ident) => ident,
+            _ => unreachable!("Invalid callee {:?}", callee),
+        };
+
+        // Intrinsics can be shadowed by a function with the same name.
+        if self.get_function(&ident.name).is_none() {
+            match ident.name.as_str() {
+                "len" => return self.build_len(&args[0]),
+                "print" => return self.build_print(&args[0]),
+                _ => {}
+            }
+        }
+
+        // The semantic analysis checks the arguments against the signature of the function.
+        let fn_value = self
+            .get_function(&ident.name)
+            .unwrap_or_else(|| unreachable!("Unknown function {}", ident.name));
+        let values = args
+            .iter()
+            .map(|arg| self.build_expr(arg))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Calls to `void` functions evaluate to an empty struct. The semantic analysis ensures
+        // that this value is never used.
+        let value = self
+            .builder
+            .build_call(fn_value, &values, "tmpcall")
+            .try_as_basic_value()
+            .left()
+            .unwrap_or_else(|| self.context.const_struct(&[], false).into());
+
+        Ok(value)
+    }
+
+    /// The length of an array is known statically, the argument is only evaluated for its side
+    /// effects. The length of a string is its number of bytes.
+    fn build_len(&mut self, arg: &Expr) -> Result<BasicValueEnum<'ctx>, CodegenError> {
+        let value = self.build_expr(arg)?;
+
+        let len = match self.types.expr_type(arg) {
+            Type::Array((_, len)) => self.context.i64_type().const_int(*len, false),
+            Type::Str | Type::Slice(_) => self.build_slice_parts(value.into_struct_value()).1,
+            ty => unreachable!("Length of '{}'", ty),
+        };
+
+        Ok(len.into())
+    }
+
+    /// Write a string to the standard output.
+    fn build_print(&mut self, arg: &Expr) -> Result<BasicValueEnum<'ctx>, CodegenError> {
+        let value = self.build_expr(arg)?.into_struct_value();
+        let (ptr, len) = self.build_slice_parts(value);
+
+        let stdout = self.context.i32_type().const_int(1, false);
+        self.builder.build_call(
+            self.get_write_function(),
+            &[stdout.into(), ptr.into(), len.into()],
+            "",
+        );
+
+        Ok(self.context.const_struct(&[], false).into())
+    }
+
+    fn build_lit(&self, lit: &Lit, ty: Type) -> BasicValueEnum<'ctx> {
+        match lit.kind {
+            LitKind::Int(value) => self
+                .get_type(ty)
+                .into_int_type()
+                .const_int(value, false)
+                .into(),
+            LitKind::Float(value) => self
+                .get_type(ty)
+                .into_float_type()
+                .const_float(value)
+                .into(),
+            LitKind::Bool(value) => {
+                let int_value = if value { 1 } else { 0 };
+                self.context.bool_type().const_int(int_value, false).into()
+            }
+            LitKind::Str(ref value) => self.build_str_lit(value),
+            LitKind::Char(value) => self.context.i8_type().const_int(value as u64, false).into(),
+        }
+    }
+
+    /// The bytes of a string literal are stored in a private constant global, and the string
+    /// points to them.
+    fn build_str_lit(&self, value: &str) -> BasicValueEnum<'ctx> {
+        let i8_type = self.context.i8_type();
+        let i64_type = self.context.i64_type();
+
+        let bytes = value
+            .bytes()
+            .map(|byte| i8_type.const_int(byte as u64, false))
+            .collect::<Vec<_>>();
+        let bytes = i8_type.const_array(&bytes);
+
+        let global = self.module.add_global(bytes.get_type(), None, "str");
+        global.set_initializer(&bytes);
+        global.set_constant(true);
+        global.set_linkage(Linkage::Private);
+        global.set_unnamed_addr(true);
+
+        // Safety: the indexes point to the first byte of the global.
+        let zero = i64_type.const_zero();
+        let ptr = unsafe { global.as_pointer_value().const_in_bounds_gep(&[zero, zero]) };
+        let len = i64_type.const_int(value.len() as u64, false);
+
+        self.get_type(Type::Str)
+            .into_struct_type()
+            .const_named_struct(&[ptr.into(), len.into()])
+            .into()
+    }
+
+    fn build_ident(&self, ident: &Ident) -> BasicValueEnum<'ctx> {
+        let ptr = self.lookup_var(ident);
+        self.builder.build_load(ptr, &ident.name)
+    }
+
+    /// Returns the index of a field in the struct of the `base` expression.
+    fn field_index(&self, base: &Expr, field: &Ident) -> u32 {
+        match self.types.expr_type(base) {
+            Type::Struct(name) => {
+                let (index, _) = self
+                    .types
+                    .struct_def(name)
+                    .field(&field.name)
+                    .expect("Unknown field");
+                index as u32
+            }
+            ty => unreachable!("Field access on '{}'", ty),
+        }
+    }
+
+    fn build_field(
+        &mut self,
+        base: &Expr,
+        field: &Ident,
+    ) -> Result<BasicValueEnum<'ctx>, CodegenError> {
+        // Fields of a place are loaded through a pointer to the field, while the other structs
+        // are only available as values.
+        if base.is_place() {
+            let ptr = self.build_field_ptr(base, field)?;
+            return Ok(self.builder.build_load(ptr, &field.name));
+        }
+
+        let value = self.build_expr(base)?.into_struct_value();
+        let value = self
+            .builder
+            .build_extract_value(value, self.field_index(base, field), &field.name)
+            .expect("Invalid field index");
+
+        Ok(value)
+    }
+
+    fn build_field_ptr(
+        &mut self,
+        base: &Expr,
+        field: &Ident,
+    ) -> Result<PointerValue<'ctx>, CodegenError> {
+        let ptr = self.build_place(base)?;
+        let field_ptr = self
+            .builder
+            .build_struct_gep(ptr, self.field_index(base, field), &field.name)
+            .expect("Invalid field index");
+
+        Ok(field_ptr)
+    }
+
+    fn build_struct_literal(
+        &mut self,
+        fields: &[(Ident, Box<Expr>)],
+        ty: Type,
+    ) -> Result<BasicValueEnum<'ctx>, CodegenError> {
+        let types = self.types;
+        let def = match ty {
+            Type::Struct(name) => types.struct_def(name),
+            _ => unreachable!("Struct literal of type '{}'", ty),
+        };
+
+        // The initializers are evaluated in the order they are written, and inserted at the
+        // position of their field.
+        let mut value = self.get_type(ty).into_struct_type().get_undef();
+        for (field, expr) in fields {
+            let field_value = self.build_expr(expr)?;
+            let (index, _) = def.field(&field.name).expect("Unknown field");
+
+            value = self
+                .builder
+                .build_insert_value(value, field_value, index as u32, "tmpinsert")
+                .expect("Invalid field index")
+                .into_struct_value();
+        }
+
+        Ok(value.into())
+    }
+
+    fn build_index(
+        &mut self,
+        base: &Expr,
+        index: &Expr,
+        location: Location,
+    ) -> Result<BasicValueEnum<'ctx>, CodegenError> {
+        let ptr = self.build_element_ptr(base, index, location)?;
+        Ok(self.builder.build_load(ptr, "tmpindex"))
+    }
+
+    fn build_element_ptr(
+        &mut self,
+        base: &Expr,
+        index: &Expr,
+        location: Location,
+    ) -> Result<PointerValue<'ctx>, CodegenError> {
+        let (ptr, len) = self.build_elements(base)?;
+        let index_value = self.build_index_value(index)?;
+
+        if self.bounds_checks {
+            let is_in_bounds =
+                self.builder
+                    .build_int_compare(IntPredicate::ULT, index_value, len, "inbounds");
+            self.build_bounds_check(is_in_bounds, "index out of bounds", location);
+        }
+
+        // Safety: the index is either checked above to be in bounds, or the checks were
+        // explicitly disabled.
+        let element_ptr = unsafe {
+            self.builder
+                .build_in_bounds_gep(ptr, &[index_value], "tmpelem")
+        };
+
+        Ok(element_ptr)
+    }
+
+    /// Returns the pointer to the first element and the length of an array, a slice or a
+    /// string.
+    fn build_elements(
+        &mut self,
+        base: &Expr,
+    ) -> Result<(PointerValue<'ctx>, IntValue<'ctx>), CodegenError> {
+        let i64_type = self.context.i64_type();
+
+        // The elements of slices and strings are accessed through the pointer they hold, while
+        // the elements of an array are accessed through a pointer to the array.
+        match self.types.expr_type(base) {
+            Type::Str | Type::Slice(_) => {
+                let value = self.build_expr(base)?.into_struct_value();
+                Ok(self.build_slice_parts(value))
+            }
+            Type::Array((_, len)) => {
+                // Elements of a place are accessed through the place, while the other arrays are
+                // stored in a stack slot so that they can be indexed with a value only known at
+                // runtime.
+                let ptr = if base.is_place() {
+                    self.build_place(base)?
+                } else {
+                    let value = self.build_expr(base)?;
+                    let ptr = self.build_entry_alloca(value.get_type(), "tmparray");
+                    self.builder.build_store(ptr, value);
+                    ptr
+                };
+
+                // Safety: the indexes point to the first element of the array.
+                let zero = i64_type.const_zero();
+                let ptr = unsafe {
+                    self.builder
+                        .build_in_bounds_gep(ptr, &[zero, zero], "tmpelems")
+                };
+
+                Ok((ptr, i64_type.const_int(*len, false)))
+            }
+            ty => unreachable!("Elements of '{}'", ty),
+        }
+    }
+
+    /// Indexes are widened to 64 bits, so that the ones of any integer type can be compared to
+    /// the length. Negative indexes become larger than any length.
+    fn build_index_value(&mut self, index: &Expr) -> Result<IntValue<'ctx>, CodegenError> {
+        let i64_type = self.context.i64_type();
+
+        let index_value = self.build_expr(index)?.into_int_value();
+        let index_value = if self.types.expr_type(index).is_signed() {
+            self.builder
+                .build_int_s_extend_or_bit_cast(index_value, i64_type, "tmpidx")
+        } else {
+            self.builder
+                .build_int_z_extend_or_bit_cast(index_value, i64_type, "tmpidx")
+        };
+
+        Ok(index_value)
+    }
+
+    /// Slices are built from the pointer to their first element and their length. The bounds
+    /// default to the start and the end of the sliced elements.
+    fn build_slice(
+        &mut self,
+        base: &Expr,
+        start: Option<&Expr>,
+        end: Option<&Expr>,
+        slice_ty: Type,
+        location: Location,
+    ) -> Result<BasicValueEnum<'ctx>, CodegenError> {
+        let (ptr, len) = self.build_elements(base)?;
+
+        let start = match start {
+            Some(start) => self.build_index_value(start)?,
+            None => self.context.i64_type().const_zero(),
+        };
+        let end = match end {
+            Some(end) => self.build_index_value(end)?,
+            None => len,
+        };
+
+        if self.bounds_checks {
+            let is_ordered =
+                self.builder
+                    .build_int_compare(IntPredicate::ULE, start, end, "ordered");
+            let is_end_in_bounds =
+                self.builder
+                    .build_int_compare(IntPredicate::ULE, end, len, "inbounds");
+            let is_in_bounds = self
+                .builder
+                .build_and(is_ordered, is_end_in_bounds, "inbounds");
+            self.build_bounds_check(is_in_bounds, "slice out of bounds", location);
+        }
+
+        // Safety: the start is either checked above to be in bounds, or the checks were
+        // explicitly disabled.
+        let slice_ptr = unsafe { self.builder.build_in_bounds_gep(ptr, &[start], "sliceptr") };
+        let slice_len = self.builder.build_int_sub(end, start, "slicelen");
+
+        let slice_type = self.get_type(slice_ty).into_struct_type();
+        let slice = self
+            .builder
+            .build_insert_value(slice_type.get_undef(), slice_ptr, 0, "tmpslice")
+            .unwrap();
+        let slice = self
+            .builder
+            .build_insert_value(slice, slice_len, 1, "tmpslice")
+            .unwrap();
+
+        Ok(slice.into_struct_value().into())
+    }
+
+    /// Abort the program with the location of the indexing or slicing expression when it is out
+    /// of bounds.
+    fn build_bounds_check(&self, is_in_bounds: IntValue<'ctx>, error: &str, location: Location) {
+        let fn_value = self.current_fn();
+        let fail_bb = self.context.append_basic_block(fn_value, "bounds.fail");
+        let ok_bb = self.context.append_basic_block(fn_value, "bounds.ok");
+
+        self.builder
+            .build_conditional_branch(is_in_bounds, ok_bb, fail_bb);
+
+        self.builder.position_at_end(fail_bb);
+
+        let LineCol { line, col } = self.file.line_col(location.start);
+        let message = format!("{}:{}:{}: {}\n", self.file.name(), line + 1, col + 1, error);
+        let message_ptr = self
+            .builder
+            .build_global_string_ptr(&message, "bounds.msg")
+            .as_pointer_value();
+
+        let stderr = self.context.i32_type().const_int(2, false);
+        let message_len = self
+            .context
+            .i64_type()
+            .const_int(message.len() as u64, false);
+        self.builder.build_call(
+            self.get_write_function(),
+            &[stderr.into(), message_ptr.into(), message_len.into()],
+            "",
+        );
+
+        let abort = self.get_libc_function("abort", self.context.void_type().fn_type(&[], false));
+        self.builder.build_call(abort, &[], "");
+        self.builder.build_unreachable();
+
+        self.builder.position_at_end(ok_bb);
+    }
+
+    fn build_array_literal(
+        &mut self,
+        elements: &[Box<Expr>],
+        ty: Type,
+    ) -> Result<BasicValueEnum<'ctx>, CodegenError> {
+        let mut value = self.get_type(ty).into_array_type().get_undef();
+        for (index, expr) in elements.iter().enumerate() {
+            let element_value = self.build_expr(expr)?;
+
+            value = self
+                .builder
+                .build_insert_value(value, element_value, index as u32, "tmpinsert")
+                .expect("Invalid element index")
+                .into_array_value();
+        }
+
+        Ok(value.into())
+    }
+
+    fn build_place(&mut self, expr: &Expr) -> Result<PointerValue<'ctx>, CodegenError> {
+        match &expr.kind {
+            ExprKind::Ident(ident) => Ok(self.lookup_var(ident)),
+            ExprKind::Field(base, field) => self.build_field_ptr(base, field),
+            ExprKind::Index(base, index) => self.build_element_ptr(base, index, expr.location),
+            _ => unreachable!("Invalid assignment target {:?}", expr),
+        }
+    }
+
+    fn build_assign(
+        &mut self,
+        op: Option<&BinaryOp>,
+        target: &Expr,
+        value: &Expr,
+    ) -> Result<BasicValueEnum<'ctx>, CodegenError> {
+        let value = self.build_expr(value)?;
+        let ptr = self.build_place(target)?;
+
+        let value = match op {
+            Some(op) => {
+                let current = self.builder.build_load(ptr, "tmpload");
+                self.build_binary_op(op, self.types.expr_type(target), current, value)
+            }
+            None => value,
+        };
+
+        self.builder.build_store(ptr, value);
+        Ok(value)
+    }
+
+    fn build_expr(&mut self, expr: &Expr) -> Result<BasicValueEnum<'ctx>, CodegenError> {
+        // The instruction computing the value of an expression is built after the ones of its
+        // operands, so the location of the expression is restored once they are built.
+        let parent_location = self.set_debug_location(expr.location);
+
+        let value = match &expr.kind {
+            ExprKind::Assign(target, value) => self.build_assign(None, target, value),
+            ExprKind::AssignOp(op, target, value) => self.build_assign(Some(op), target, value),
+            ExprKind::Logical(op, left, right) => self.build_logical(op, left, right),
+            ExprKind::Binary(op, left, right) => self.build_binary(op, left, right),
+            ExprKind::Unary(op, expr) => self.build_unary(op, expr),
+            ExprKind::If(condition, then_expr, else_expr) => {
+                self.build_if_expr(condition, then_expr, else_expr)
+            }
+            ExprKind::Ident(ident) => Ok(self.build_ident(ident)),
+            ExprKind::Call(callee, args) => self.build_call(callee, args),
+            ExprKind::Field(base, field) => self.build_field(base, field),
+            ExprKind::Index(base, index) => self.build_index(base, index, expr.location),
+            ExprKind::Slice(base, start, end) => self.build_slice(
+                base,
+                start.as_deref(),
+                end.as_deref(),
+                self.types.expr_type(expr),
+                expr.location,
+            ),
+            ExprKind::Struct(_, fields) => {
+                self.build_struct_literal(fields, self.types.expr_type(expr))
+            }
+            ExprKind::Array(elements) => {
+                self.build_array_literal(elements, self.types.expr_type(expr))
+            }
+            ExprKind::Lit(lit) => Ok(self.build_lit(lit, self.types.expr_type(expr))),
+        };
+
+        if let Some(location) = parent_location {
+            self.set_debug_location(location);
+        }
+
+        value
+    }
+
+    fn build_decl(&mut self, decl: &Decl) -> Result<(), CodegenError> {
+        match &decl.kind {
+            DeclKind::Var(ident, _, init) => {
+                let value = match init {
+                    Some(expr) => Some(self.build_expr(expr)?),
+                    None => None,
+                };
+
+                let ty = self.types.var_type(ident);
+                let ptr = self.build_entry_alloca(self.get_type(ty), &ident.name);
+                if let Some(value) = value {
+                    self.builder.build_store(ptr, value);
+                }
+
+                if let Some(debug_info) = &self.debug_info {
+                    let block = self.builder.get_insert_block().unwrap();
+                    debug_info.declare_var(ptr, &ident.name, ty, None, ident.location, block);
+                }
+
+                // The variable is only declared once its initializer is evaluated, so that
+                // `let a = a + 1;` refers to the shadowed `a`.
+                self.declare_var(&ident.name, ptr);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn build_stmt(&mut self, stmt: &Stmt) -> Result<(), CodegenError> {
+        self.set_debug_location(stmt.location);
+
+        match &stmt.kind {
+            StmtKind::Decl(decl) => self.build_decl(decl)?,
+            StmtKind::Ret(expr) => {
+                let value = match expr {
+                    Some(expr) => Some(self.build_expr(expr)?),
+                    None => None,
+                };
+
+                self.builder
+                    .build_return(value.as_ref().map(|value| value as &dyn BasicValue));
+            }
+            StmtKind::If(condition, then_block, else_stmt) => {
+                self.build_if(condition, then_block, else_stmt.as_deref())?
+            }
+            StmtKind::While(label, condition, body) => {
+                self.build_while(label.as_ref(), condition, body)?
+            }
+            StmtKind::Loop(label, body) => self.build_loop(label.as_ref(), body)?,
+            StmtKind::Break(label) => self.build_break(label.as_ref()),
+            StmtKind::Continue(label) => self.build_continue(label.as_ref()),
+            StmtKind::Block(block) => self.build_block(block)?,
+            StmtKind::Expr(expr) => {
+                self.build_expr(expr)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn build_if(
+        &mut self,
+        condition: &Expr,
+        then_block: &Block,
+        else_stmt: Option<&Stmt>,
+    ) -> Result<(), CodegenError> {
+        let condition = self.build_expr(condition)?.into_int_value();
+
+        let fn_value = self.current_fn();
+        let then_bb = self.context.append_basic_block(fn_value, "then");
+        let else_bb = self.context.append_basic_block(fn_value, "else");
+        self.builder
+            .build_conditional_branch(condition, then_bb, else_bb);
+
+        // The merge block is only created when one of the branches falls through. When both
+        // branches are terminated, the code following the conditional is unreachable.
+        let mut merge_bb = None;
+
+        self.builder.position_at_end(then_bb);
+        self.build_block(then_block)?;
+        self.build_merge_branch(fn_value, &mut merge_bb);
+
+        self.builder.position_at_end(else_bb);
+        if let Some(stmt) = else_stmt {
+            self.build_stmt(stmt)?;
+        }
+        self.build_merge_branch(fn_value, &mut merge_bb);
+
+        if let Some(merge_bb) = merge_bb {
+            self.builder.position_at_end(merge_bb);
+        }
+
+        Ok(())
+    }
+
+    fn build_merge_branch(
+        &self,
+        fn_value: FunctionValue<'ctx>,
+        merge_bb: &mut Option<BasicBlock<'ctx>>,
+    ) {
+        if !self.is_terminated() {
+            let merge_bb =
+                *merge_bb.get_or_insert_with(|| self.context.append_basic_block(fn_value, "merge"));
+            self.builder.build_unconditional_branch(merge_bb);
+        }
+    }
+
+    fn build_while(
+        &mut self,
+        label: Option<&Ident>,
+        condition: &Expr,
+        body: &Block,
+    ) -> Result<(), CodegenError> {
+        let fn_value = self.current_fn();
+        let cond_bb = self.context.append_basic_block(fn_value, "while.cond");
+        let body_bb = self.context.append_basic_block(fn_value, "while.body");
+        let end_bb = self.context.append_basic_block(fn_value, "while.end");
+
+        self.builder.build_unconditional_branch(cond_bb);
+
+        self.builder.position_at_end(cond_bb);
+        let condition = self.build_expr(condition)?.into_int_value();
+        self.builder
+            .build_conditional_branch(condition, body_bb, end_bb);
+
+        self.builder.position_at_end(body_bb);
+        self.build_loop_body(label, body, cond_bb, Some(end_bb))?;
+
+        self.builder.position_at_end(end_bb);
+
+        Ok(())
+    }
+
+    fn build_loop(&mut self, label: Option<&Ident>, body: &Block) -> Result<(), CodegenError> {
+        let fn_value = self.current_fn();
+        let body_bb = self.context.append_basic_block(fn_value, "loop.body");
+
+        self.builder.build_unconditional_branch(body_bb);
+
+        self.builder.position_at_end(body_bb);
+
+        // The exit block is only created by a `break`. Without it, the code following the loop
+        // is unreachable.
+        if let Some(end_bb) = self.build_loop_body(label, body, body_bb, None)? {
+            self.builder.position_at_end(end_bb);
+        }
+
+        Ok(())
+    }
+
+    /// Build the body of a loop and branch back to the continue block, returning the exit block
+    /// of the loop if any.
+    fn build_loop_body(
+        &mut self,
+        label: Option<&Ident>,
+        body: &Block,
+        continue_bb: BasicBlock<'ctx>,
+        break_bb: Option<BasicBlock<'ctx>>,
+    ) -> Result<Option<BasicBlock<'ctx>>, CodegenError> {
+        self.loops.push(LoopTarget {
+            label: label.map(|label| label.name.clone()),
+            continue_bb,
+            break_bb,
+        });
+
+        let result = self.build_block(body);
+        let target = self.loops.pop().unwrap();
+        result?;
+
+        if !self.is_terminated() {
+            self.builder.build_unconditional_branch(continue_bb);
+        }
+
+        Ok(target.break_bb)
+    }
+
+    fn build_break(&mut self, label: Option<&Ident>) {
+        let fn_value = self.current_fn();
+        let context = self.context;
+
+        let target = self.find_loop(label);
+        let break_bb = *target
+            .break_bb
+            .get_or_insert_with(|| context.append_basic_block(fn_value, "loop.end"));
+
+        self.builder.build_unconditional_branch(break_bb);
+    }
+
+    fn build_continue(&mut self, label: Option<&Ident>) {
+        let continue_bb = self.find_loop(label).continue_bb;
+        self.builder.build_unconditional_branch(continue_bb);
+    }
+
+    fn find_loop(&mut self, label: Option<&Ident>) -> &mut LoopTarget<'ctx> {
+        let target = match label {
+            Some(label) => self
+                .loops
+                .iter_mut()
+                .rev()
+                .find(|target| target.label.as_ref() == Some(&label.name)),
+            None => self.loops.last_mut(),
+        };
+
+        target.expect("Loop control statement outside of a loop")
+    }
+
+    fn build_block(&mut self, block: &Block) -> Result<(), CodegenError> {
+        if let Some(debug_info) = &mut self.debug_info {
+            debug_info.enter_block(block.location);
+        }
+
+        self.enter_scope();
+        let result = self.build_stmts(&block.stmts);
+        self.exit_scope();
+
+        if let Some(debug_info) = &mut self.debug_info {
+            debug_info.exit_scope();
+        }
+
+        result
+    }
+
+    fn build_stmts(&mut self, stmts: &[Stmt]) -> Result<(), CodegenError> {
+        for stmt in stmts {
+            // Statements following a terminator are dead code and can't be appended to the
+            // current basic block.
+            if self.is_terminated() {
+                break;
+            }
+
+            self.build_stmt(stmt)?;
+        }
+
+        Ok(())
+    }
+
+    fn is_terminated(&self) -> bool {
+        self.builder
+            .get_insert_block()
+            .and_then(|block| block.get_terminator())
+            .is_some()
+    }
+
+    fn define_struct(&self, ident: &Ident) {
+        let fields_type = self
+            .types
+            .struct_def(&ident.name)
+            .fields
+            .iter()
+            .map(|(_, ty)| self.get_type(*ty))
+            .collect::<Vec<BasicTypeEnum>>();
+
+        self.module
+            .get_struct_type(&ident.name)
+            .expect("Struct defined before being declared")
+            .set_body(&fields_type, false);
+    }
+
+    fn declare_fn(&self, ident: &Ident) -> FunctionValue<'ctx> {
+        let sig = self.types.fn_sig(&ident.name);
+
+        let params_type = sig
+            .params
+            .iter()
+            .map(|ty| self.get_type(*ty))
+            .collect::<Vec<BasicTypeEnum>>();
+
+        let fn_type = match sig.ret {
+            Type::Void => self.context.void_type().fn_type(&params_type, false),
+            ret => self.get_type(ret).fn_type(&params_type, false),
+        };
+        self.module.add_function(&ident.name, fn_type, None)
+    }
+
+    fn build_fn(
+        &mut self,
+        ident: &Ident,
+        params: &[(Ident, Ty)],
+        body: &Block,
+    ) -> Result<(), CodegenError> {
+        let fn_value = self
+            .get_function(&ident.name)
+            .expect("Function built before being declared");
+
+        let entry = self.context.append_basic_block(fn_value, "entry");
+        self.builder.position_at_end(entry);
+
+        let sig = self.types.fn_sig(&ident.name);
+        if let Some(debug_info) = &mut self.debug_info {
+            debug_info.enter_fn(
+                fn_value,
+                &ident.name,
+                &sig.params,
+                sig.ret,
+                ident.location,
+                body.location,
+            );
+        }
+        self.set_debug_location(ident.location);
+
+        self.enter_scope();
+
+        let params = params.iter().zip(fn_value.get_param_iter());
+        for (index, ((ident, _), value)) in params.enumerate() {
+            let ptr = self.build_entry_alloca(value.get_type(), &ident.name);
+            self.builder.build_store(ptr, value);
+            self.declare_var(&ident.name, ptr);
+
+            if let Some(debug_info) = &self.debug_info {
+                let arg_no = index as u32 + 1;
+                debug_info.declare_var(
+                    ptr,
+                    &ident.name,
+                    sig.params[index],
+                    Some(arg_no),
+                    ident.location,
+                    entry,
+                );
+            }
+        }
+
+        let result = self.build_block(body);
+
+        // `void` functions implicitly return when reaching the end of their body.
+        if result.is_ok() && sig.ret == Type::Void && !self.is_terminated() {
+            self.builder.build_return(None);
+        }
+
+        self.exit_scope();
+
+        if let Some(debug_info) = &mut self.debug_info {
+            debug_info.exit_scope();
+        }
+
+        result
+    }
+
+    fn build_module(&mut self) -> Result<(), Vec<CodegenError>> {
+        let program = self.program;
+        let mut errors = Vec::new();
+
+        // The struct types are created before their bodies are set, so that fields can refer to
+        // any struct regardless of where it is declared in the file.
+        for decl in &program.decls {
+            if let TopLevelDeclKind::Struct(ident, _) = &decl.kind {
+                self.context.opaque_struct_type(&ident.name);
+            }
+        }
+
+        for decl in &program.decls {
+            if let TopLevelDeclKind::Struct(ident, _) = &decl.kind {
+                self.define_struct(ident);
+            }
+        }
+
+        // All the functions are declared upfront, so that a function body can call any function
+        // regardless of where it is declared in the file.
+        for decl in &program.decls {
+            if let TopLevelDeclKind::Fn(ident, ..) = &decl.kind {
+                self.declare_fn(ident);
+            }
+        }
+
+        for decl in &program.decls {
+            if let TopLevelDeclKind::Fn(ident, params, _, body) = &decl.kind {
+                if let Err(err) = self.build_fn(ident, params, body) {
+                    errors.push(err);
+                }
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        if let Some(debug_info) = &self.debug_info {
+            debug_info.finalize();
+        }
+
+        self.module.verify().map_err(|err| {
+            vec![CodegenError::new(
+                CodegenErrorKind::InvalidModule(err.to_string()),
+                program.location,
+            )]
+        })
+    }
+}
+
+/// Generate the LLVM module named `name` for the program of a source file, using the types
+/// resolved by the semantic analysis. The module targets `machine` when it is provided, and
+/// has no target otherwise.
+pub fn compile<'ctx>(
+    context: &'ctx Context,
+    name: &str,
+    file: &'ctx SourceFile,
+    program: &'ctx Program,
+    types: &'ctx TypeTable,
+    machine: Option<&TargetMachine>,
+    options: CodegenOptions,
+) -> Result<Module<'ctx>, Vec<CodegenError>> {
+    let mut code_gen = CodeGen::new(name, file, program, types, context, machine, options);
+    code_gen.build_module()?;
+
+    Ok(code_gen.module)
+}
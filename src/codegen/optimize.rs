@@ -0,0 +1,244 @@
+use std::{
+    fmt,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use inkwell::{module::Module, passes::PassManager, OptimizationLevel};
+
+/// Optimization level selected with the `-O` flag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptLevel {
+    O0,
+    O1,
+    O2,
+    O3,
+    /// Optimize for size.
+    Os,
+}
+
+impl FromStr for OptLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "0" => Ok(OptLevel::O0),
+            "1" => Ok(OptLevel::O1),
+            "2" => Ok(OptLevel::O2),
+            "3" => Ok(OptLevel::O3),
+            "s" => Ok(OptLevel::Os),
+            _ => Err(format!(
+                "Invalid optimization level '{}', expected one of 0, 1, 2, 3 or s",
+                s
+            )),
+        }
+    }
+}
+
+impl From<OptLevel> for OptimizationLevel {
+    fn from(level: OptLevel) -> Self {
+        match level {
+            OptLevel::O0 => OptimizationLevel::None,
+            OptLevel::O1 => OptimizationLevel::Less,
+            OptLevel::O2 | OptLevel::Os => OptimizationLevel::Default,
+            OptLevel::O3 => OptimizationLevel::Aggressive,
+        }
+    }
+}
+
+/// LLVM pass that can be part of the optimization pipeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pass {
+    FunctionAttrs,
+    Inliner,
+    AlwaysInliner,
+    Ipsccp,
+    DeadArgElimination,
+    Mem2Reg,
+    Sccp,
+    InstCombine,
+    Reassociate,
+    EarlyCse,
+    Gvn,
+    DeadStoreElimination,
+    AggressiveDce,
+    SimplifyCfg,
+    TailCallElimination,
+    LoopRotate,
+    Licm,
+    IndVarSimplify,
+    LoopDeletion,
+    LoopUnroll,
+    GlobalDce,
+}
+
+impl Pass {
+    fn add_to(&self, pass_manager: &PassManager<Module>) {
+        match self {
+            Pass::FunctionAttrs => pass_manager.add_function_attrs_pass(),
+            Pass::Inliner => pass_manager.add_function_inlining_pass(),
+            Pass::AlwaysInliner => pass_manager.add_always_inliner_pass(),
+            Pass::Ipsccp => pass_manager.add_ipsccp_pass(),
+            Pass::DeadArgElimination => pass_manager.add_dead_arg_elimination_pass(),
+            Pass::Mem2Reg => pass_manager.add_promote_memory_to_register_pass(),
+            Pass::Sccp => pass_manager.add_sccp_pass(),
+            Pass::InstCombine => pass_manager.add_instruction_combining_pass(),
+            Pass::Reassociate => pass_manager.add_reassociate_pass(),
+            Pass::EarlyCse => pass_manager.add_early_cse_pass(),
+            Pass::Gvn => pass_manager.add_gvn_pass(),
+            Pass::DeadStoreElimination => pass_manager.add_dead_store_elimination_pass(),
+            Pass::AggressiveDce => pass_manager.add_aggressive_dce_pass(),
+            Pass::SimplifyCfg => pass_manager.add_cfg_simplification_pass(),
+            Pass::TailCallElimination => pass_manager.add_tail_call_elimination_pass(),
+            Pass::LoopRotate => pass_manager.add_loop_rotate_pass(),
+            Pass::Licm => pass_manager.add_licm_pass(),
+            Pass::IndVarSimplify => pass_manager.add_ind_var_simplify_pass(),
+            Pass::LoopDeletion => pass_manager.add_loop_deletion_pass(),
+            Pass::LoopUnroll => pass_manager.add_loop_unroll_pass(),
+            Pass::GlobalDce => pass_manager.add_global_dce_pass(),
+        }
+    }
+}
+
+impl fmt::Display for Pass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Names of the passes as accepted by LLVM's `opt` tool.
+        let name = match self {
+            Pass::FunctionAttrs => "function-attrs",
+            Pass::Inliner => "inline",
+            Pass::AlwaysInliner => "always-inline",
+            Pass::Ipsccp => "ipsccp",
+            Pass::DeadArgElimination => "deadargelim",
+            Pass::Mem2Reg => "mem2reg",
+            Pass::Sccp => "sccp",
+            Pass::InstCombine => "instcombine",
+            Pass::Reassociate => "reassociate",
+            Pass::EarlyCse => "early-cse",
+            Pass::Gvn => "gvn",
+            Pass::DeadStoreElimination => "dse",
+            Pass::AggressiveDce => "adce",
+            Pass::SimplifyCfg => "simplifycfg",
+            Pass::TailCallElimination => "tailcallelim",
+            Pass::LoopRotate => "loop-rotate",
+            Pass::Licm => "licm",
+            Pass::IndVarSimplify => "indvars",
+            Pass::LoopDeletion => "loop-deletion",
+            Pass::LoopUnroll => "loop-unroll",
+            Pass::GlobalDce => "globaldce",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// Returns the passes run for an optimization level, in order.
+pub fn pipeline(level: OptLevel) -> Vec<Pass> {
+    use Pass::*;
+
+    match level {
+        OptLevel::O0 => vec![],
+        OptLevel::O1 => vec![Mem2Reg, InstCombine, Reassociate, SimplifyCfg],
+        OptLevel::O2 => vec![
+            FunctionAttrs,
+            Inliner,
+            Mem2Reg,
+            Sccp,
+            InstCombine,
+            Reassociate,
+            EarlyCse,
+            Gvn,
+            DeadStoreElimination,
+            AggressiveDce,
+            SimplifyCfg,
+            GlobalDce,
+        ],
+        OptLevel::O3 => vec![
+            FunctionAttrs,
+            Ipsccp,
+            DeadArgElimination,
+            Inliner,
+            Mem2Reg,
+            Sccp,
+            InstCombine,
+            Reassociate,
+            EarlyCse,
+            Gvn,
+            TailCallElimination,
+            LoopRotate,
+            Licm,
+            IndVarSimplify,
+            LoopDeletion,
+            LoopUnroll,
+            InstCombine,
+            DeadStoreElimination,
+            AggressiveDce,
+            SimplifyCfg,
+            GlobalDce,
+        ],
+        // Inlining and loop unrolling are avoided as they trade code size for speed.
+        OptLevel::Os => vec![
+            FunctionAttrs,
+            AlwaysInliner,
+            Mem2Reg,
+            Sccp,
+            InstCombine,
+            Reassociate,
+            EarlyCse,
+            Gvn,
+            DeadStoreElimination,
+            AggressiveDce,
+            SimplifyCfg,
+            GlobalDce,
+        ],
+    }
+}
+
+/// Time spent optimizing a module.
+#[derive(Debug)]
+pub struct Timings {
+    /// Time spent in each pass when run on its own over a copy of the module. In the pipeline,
+    /// the passes share their analyses and the function passes are interleaved, so these are
+    /// only approximations.
+    pub passes: Vec<(Pass, Duration)>,
+    /// Time spent running the pipeline over the module.
+    pub total: Duration,
+}
+
+fn create_pass_manager<'ctx>(passes: &[Pass]) -> PassManager<Module<'ctx>> {
+    let pass_manager = PassManager::create(());
+    for pass in passes {
+        pass.add_to(&pass_manager);
+    }
+
+    pass_manager
+}
+
+/// Run the passes over the module.
+pub fn optimize(module: &Module, passes: &[Pass]) {
+    create_pass_manager(passes).run_on(module);
+}
+
+/// Run the passes over the module like `optimize`, measuring the time spent in the pipeline and
+/// an estimate of the time spent in each pass.
+pub fn optimize_timed(module: &Module, passes: &[Pass]) -> Timings {
+    // The passes are timed over a copy, to leave the module untouched for the pipeline.
+    let copy = module.clone();
+    let pass_timings = passes
+        .iter()
+        .map(|pass| {
+            let pass_manager = create_pass_manager(&[*pass]);
+            let start = Instant::now();
+            pass_manager.run_on(&copy);
+            (*pass, start.elapsed())
+        })
+        .collect();
+
+    let pass_manager = create_pass_manager(passes);
+    let start = Instant::now();
+    pass_manager.run_on(module);
+
+    Timings {
+        passes: pass_timings,
+        total: start.elapsed(),
+    }
+}
@@ -0,0 +1,78 @@
+use std::path::Path;
+
+use inkwell::{
+    module::Module,
+    targets::{
+        CodeModel, FileType, InitializationConfig, RelocMode, Target, TargetMachine, TargetTriple,
+    },
+    OptimizationLevel,
+};
+
+/// Create the target machine for a target triple, or for the host when no triple is provided.
+pub fn create_target_machine(
+    triple: Option<&str>,
+    level: OptimizationLevel,
+) -> Result<TargetMachine, String> {
+    Target::initialize_all(&InitializationConfig::default());
+
+    // The host CPU features are only relevant when compiling for the host.
+    let (triple, cpu, features) = match triple {
+        Some(triple) => (
+            TargetTriple::create(triple),
+            "generic".to_owned(),
+            String::new(),
+        ),
+        None => (
+            TargetMachine::get_default_triple(),
+            TargetMachine::get_host_cpu_name().to_string(),
+            TargetMachine::get_host_cpu_features().to_string(),
+        ),
+    };
+
+    let target = Target::from_triple(&triple).map_err(|err| err.to_string())?;
+
+    target
+        .create_target_machine(
+            &triple,
+            &cpu,
+            &features,
+            level,
+            RelocMode::PIC,
+            CodeModel::Default,
+        )
+        .ok_or_else(|| format!("Unsupported target '{}'", triple.as_str().to_string_lossy()))
+}
+
+/// Set the target triple and the data layout of the module to the ones of the target machine.
+pub fn configure_module(module: &Module, machine: &TargetMachine) {
+    module.set_triple(&machine.get_triple());
+    module.set_data_layout(&machine.get_target_data().get_data_layout());
+}
+
+/// Write the module as a native object file.
+pub fn write_object_file(
+    module: &Module,
+    machine: &TargetMachine,
+    path: &Path,
+) -> Result<(), String> {
+    machine
+        .write_to_file(module, FileType::Object, path)
+        .map_err(|err| err.to_string())
+}
+
+/// Check that the module defines the `fn main() -> int` function used as the entry point of an
+/// executable.
+pub fn check_entry_point(module: &Module) -> Result<(), String> {
+    let is_valid = module.get_function("main").is_some_and(|main| {
+        let fn_type = main.get_type();
+
+        fn_type.count_param_types() == 0
+            && fn_type.get_return_type() == Some(module.get_context().i32_type().into())
+    });
+
+    if is_valid {
+        Ok(())
+    } else {
+        Err("Missing entry point. An executable requires a 'fn main() -> int' function.".to_owned())
+    }
+}
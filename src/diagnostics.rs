@@ -0,0 +1,128 @@
+use annotate_snippets::{
+    display_list::{DisplayList, FormatOptions},
+    snippet::{Annotation, AnnotationType, Slice, Snippet, SourceAnnotation},
+};
+
+use crate::{
+    ast::location::SourceFile,
+    codegen::error::CodegenError,
+    parser::error::{ParsingError, ParsingErrorLocation},
+    sema::error::SemaError,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl From<Severity> for AnnotationType {
+    fn from(severity: Severity) -> Self {
+        match severity {
+            Severity::Error => AnnotationType::Error,
+            Severity::Warning => AnnotationType::Warning,
+        }
+    }
+}
+
+/// A message reported to the user, pointing to a range of the source file.
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    /// Byte offsets of the start and the end of the range. An empty range points to a position.
+    pub span: (usize, usize),
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, message: String, span: (usize, usize)) -> Self {
+        Diagnostic {
+            severity,
+            message,
+            span,
+        }
+    }
+
+    /// Render the diagnostic along with the source lines it points to, in the same format as
+    /// rustc.
+    pub fn render(&self, file: &SourceFile, color: bool) -> String {
+        let source = file.src();
+        let start = self.span.0.min(source.len());
+        let end = self.span.1.clamp(start, source.len());
+
+        // Only the lines covered by the span are displayed.
+        let first_line = file.line_index(start);
+        let (slice_start, _) = file.line_range(first_line);
+        let (_, slice_end) = file.line_range(file.line_index(end));
+        let mut slice = source[slice_start..slice_end].to_owned();
+
+        // Annotation ranges are expressed in characters relative to the slice. A position is
+        // underlined as a single character.
+        let range_start = source[slice_start..start].chars().count();
+        let range_end = range_start + source[start..end].chars().count().max(1);
+
+        // A position at the end of the line points past the last character, which needs to
+        // exist in the slice to be underlined.
+        if range_end > slice.chars().count() {
+            slice.push(' ');
+        }
+
+        let annotation_type = AnnotationType::from(self.severity);
+        let snippet = Snippet {
+            title: Some(Annotation {
+                id: None,
+                label: Some(&self.message),
+                annotation_type,
+            }),
+            footer: vec![],
+            slices: vec![Slice {
+                source: &slice,
+                line_start: first_line + 1,
+                origin: Some(file.name()),
+                fold: false,
+                annotations: vec![SourceAnnotation {
+                    range: (range_start, range_end),
+                    label: "",
+                    annotation_type,
+                }],
+            }],
+            opt: FormatOptions {
+                color,
+                ..Default::default()
+            },
+        };
+
+        DisplayList::from(snippet).to_string()
+    }
+}
+
+impl From<&ParsingError> for Diagnostic {
+    fn from(err: &ParsingError) -> Self {
+        let span = match err.location {
+            ParsingErrorLocation::Position(pos) => (pos, pos),
+            ParsingErrorLocation::Span(span) => span,
+        };
+
+        Diagnostic::new(Severity::Error, err.kind.to_string(), span)
+    }
+}
+
+impl From<&SemaError> for Diagnostic {
+    fn from(err: &SemaError) -> Self {
+        let span = (err.location.start, err.location.end);
+        let severity = if err.is_warning() {
+            Severity::Warning
+        } else {
+            Severity::Error
+        };
+
+        Diagnostic::new(severity, err.kind.to_string(), span)
+    }
+}
+
+impl From<&CodegenError> for Diagnostic {
+    fn from(err: &CodegenError) -> Self {
+        let span = (err.location.start, err.location.end);
+        Diagnostic::new(Severity::Error, err.kind.to_string(), span)
+    }
+}
@@ -7,4 +7,6 @@ extern crate lazy_static;
 
 pub mod ast;
 pub mod codegen;
+pub mod diagnostics;
 pub mod parser;
+pub mod sema;
@@ -1,19 +1,40 @@
-use std::{fs, path::PathBuf, process};
+use std::{
+    env, fs,
+    io::{self, IsTerminal},
+    path::{Path, PathBuf},
+    process,
+};
 
-use clap::Parser;
-use turbo_bear::{codegen, parser};
+use clap::{AppSettings, ArgEnum, Args, Parser, Subcommand};
+use inkwell::{context::Context, module::Module, targets::TargetMachine, OptimizationLevel};
+use turbo_bear::{
+    ast::ast::Program,
+    ast::location::{SourceFile, SourceMap},
+    codegen::{
+        self, jit,
+        optimize::{self, OptLevel},
+        target, CodegenOptions,
+    },
+    diagnostics::Diagnostic,
+    parser,
+    sema::{self, TypeTable},
+};
 
 #[derive(Parser, Debug)]
 #[clap(
     name = "turbo-bear",
     author = "Pierre-Marie Dartus <pm@dartus.fr>",
     about = "Compiler for the turbo bear language",
-    version = env!("CARGO_PKG_VERSION")
+    version = env!("CARGO_PKG_VERSION"),
+    setting = AppSettings::SubcommandsNegateReqs
 )]
 struct Opts {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
     /// The file to compile
-    #[clap(parse(from_os_str))]
-    input: PathBuf,
+    #[clap(parse(from_os_str), required = true)]
+    input: Option<PathBuf>,
 
     /// Print the parsed AST
     #[clap(long)]
@@ -22,31 +43,346 @@ struct Opts {
     /// Print the generated LLVM IR
     #[clap(long)]
     llvm_ir: bool,
+
+    /// The kind of output file to write
+    #[clap(long, arg_enum)]
+    emit: Option<Emit>,
+
+    /// The output file, defaults to the input file with the extension of the emitted kind
+    #[clap(short, long, parse(from_os_str), requires = "emit")]
+    output: Option<PathBuf>,
+
+    /// The target triple to compile for, defaults to the host
+    #[clap(long)]
+    target: Option<String>,
+
+    #[clap(flatten)]
+    codegen: CodegenOpts,
+
+    #[clap(flatten)]
+    optimize: OptimizeOpts,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Compile a file in memory and run it, exiting with the value returned by its main function
+    Run {
+        /// The file to run
+        #[clap(parse(from_os_str))]
+        input: PathBuf,
+
+        #[clap(flatten)]
+        codegen: CodegenOpts,
+
+        #[clap(flatten)]
+        optimize: OptimizeOpts,
+    },
+}
+
+#[derive(Args, Debug)]
+struct CodegenOpts {
+    /// Emit DWARF debug information
+    #[clap(short = 'g')]
+    debug: bool,
+
+    /// Code generation flags
+    #[clap(short = 'C', arg_enum, multiple_occurrences = true)]
+    flags: Vec<CodegenFlag>,
+}
+
+impl CodegenOpts {
+    fn options(&self) -> CodegenOptions {
+        CodegenOptions {
+            debug_info: self.debug,
+            bounds_checks: !self.flags.contains(&CodegenFlag::NoBoundsChecks),
+        }
+    }
+}
+
+#[derive(ArgEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum CodegenFlag {
+    /// Don't check that array indexes are in bounds
+    NoBoundsChecks,
+}
+
+#[derive(Args, Debug)]
+struct OptimizeOpts {
+    /// The optimization level: 0, 1, 2, 3 or s to optimize for size
+    #[clap(short = 'O', default_value = "0")]
+    opt_level: OptLevel,
+
+    /// Print the optimization passes run over the module
+    #[clap(long)]
+    print_passes: bool,
+
+    /// Print the time spent in the optimization pipeline, with an estimate for each pass
+    #[clap(long)]
+    time_passes: bool,
+}
+
+#[derive(ArgEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum Emit {
+    LlvmIr,
+    LlvmBc,
+    Obj,
+    Exe,
+}
+
+impl Emit {
+    fn extension(&self) -> &'static str {
+        match self {
+            Emit::LlvmIr => "ll",
+            Emit::LlvmBc => "bc",
+            Emit::Obj => "o",
+            Emit::Exe => env::consts::EXE_EXTENSION,
+        }
+    }
 }
 
 fn main() {
     let opts = Opts::parse();
 
-    let source = match fs::read_to_string(&opts.input) {
+    match &opts.command {
+        Some(Command::Run {
+            input,
+            codegen,
+            optimize,
+        }) => run(input, codegen, optimize),
+        None => compile(&opts),
+    }
+}
+
+fn run(input: &Path, codegen: &CodegenOpts, optimize: &OptimizeOpts) {
+    let mut source_map = SourceMap::new();
+    let file = load_file(&mut source_map, input);
+
+    let program = parse(file);
+    let types = check(file, &program);
+
+    // The JIT compiles for the host, whose data layout is needed to describe the debug types.
+    let machine = codegen
+        .debug
+        .then(|| create_target_machine(None, optimize.opt_level.into()));
+
+    let context = Context::create();
+    let module = build_module(
+        &context,
+        file,
+        input,
+        &program,
+        &types,
+        machine.as_ref(),
+        codegen.options(),
+    );
+    optimize_module(&module, optimize);
+
+    match jit::run_main(&module, optimize.opt_level.into()) {
+        Ok(code) => process::exit(code),
+        Err(err) => {
+            eprintln!("Failed to run {}: {}", input.to_string_lossy(), err);
+            process::exit(1);
+        }
+    }
+}
+
+fn compile(opts: &Opts) {
+    let input = opts.input.as_ref().unwrap();
+
+    let mut source_map = SourceMap::new();
+    let file = load_file(&mut source_map, input);
+
+    let program = parse(file);
+    if opts.parse {
+        println!("{:#?}", program);
+        return;
+    }
+
+    let types = check(file, &program);
+
+    // The target machine is only needed to generate native code, to describe the debug types
+    // with its data layout, or to set the target of the emitted IR when it is explicitly
+    // requested.
+    let needs_machine = matches!(opts.emit, Some(Emit::Obj | Emit::Exe)) || opts.codegen.debug;
+    let machine = (needs_machine || opts.target.is_some())
+        .then(|| create_target_machine(opts.target.as_deref(), opts.optimize.opt_level.into()));
+
+    let context = Context::create();
+    let module = build_module(
+        &context,
+        file,
+        input,
+        &program,
+        &types,
+        machine.as_ref(),
+        opts.codegen.options(),
+    );
+
+    optimize_module(&module, &opts.optimize);
+
+    if opts.llvm_ir {
+        print!("{}", module.print_to_string().to_string());
+    }
+
+    if let Some(emit) = opts.emit {
+        let output = opts
+            .output
+            .clone()
+            .unwrap_or_else(|| input.with_extension(emit.extension()));
+
+        if emit == Emit::Exe {
+            if let Err(err) = target::check_entry_point(&module) {
+                eprintln!("{}", err);
+                process::exit(1);
+            }
+        }
+
+        if let Err(err) = write_output(&module, machine.as_ref(), emit, &output) {
+            eprintln!("Failed to write file {}: {}", output.to_string_lossy(), err);
+            process::exit(1);
+        }
+    }
+}
+
+fn write_output(
+    module: &Module,
+    machine: Option<&TargetMachine>,
+    emit: Emit,
+    output: &Path,
+) -> Result<(), String> {
+    match emit {
+        Emit::LlvmIr => module.print_to_file(output).map_err(|err| err.to_string()),
+        Emit::LlvmBc => {
+            if module.write_bitcode_to_path(output) {
+                Ok(())
+            } else {
+                Err("Failed to write bitcode".to_owned())
+            }
+        }
+        Emit::Obj => target::write_object_file(module, machine.unwrap(), output),
+        Emit::Exe => {
+            let object = env::temp_dir().join(format!("turbo-bear-{}.o", process::id()));
+            target::write_object_file(module, machine.unwrap(), &object)?;
+
+            let result = link(&object, output);
+            let _ = fs::remove_file(&object);
+            result
+        }
+    }
+}
+
+/// Link an object file into an executable with the system C compiler driver.
+fn link(object: &Path, output: &Path) -> Result<(), String> {
+    let status = process::Command::new("cc")
+        .arg(object)
+        .arg("-o")
+        .arg(output)
+        .status()
+        .map_err(|err| format!("Failed to run the linker: {}", err))?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("The linker failed with {}", status))
+    }
+}
+
+fn load_file<'a>(source_map: &'a mut SourceMap, path: &Path) -> &'a SourceFile {
+    let source = match fs::read_to_string(path) {
         Ok(src) => src,
         Err(_) => {
-            eprintln!("Failed to read file {}", opts.input.to_string_lossy());
+            eprintln!("Failed to read file {}", path.to_string_lossy());
             process::exit(1);
         }
     };
 
-    let program = match parser::parse(&source) {
-        Ok(program) => program,
+    let file_id = source_map.add_file(path.to_string_lossy().into_owned(), source);
+    source_map.file(file_id)
+}
+
+fn parse(file: &SourceFile) -> Program {
+    let (program, errors) = parser::parse(file);
+    report(file, errors.iter().map(Diagnostic::from));
+
+    match program {
+        Some(program) if errors.is_empty() => program,
+        _ => process::exit(1),
+    }
+}
+
+fn check(file: &SourceFile, program: &Program) -> TypeTable {
+    let (types, errors) = sema::check(program);
+    report(file, errors.iter().map(Diagnostic::from));
+
+    match types {
+        Some(types) => types,
+        None => process::exit(1),
+    }
+}
+
+fn build_module<'ctx>(
+    context: &'ctx Context,
+    file: &'ctx SourceFile,
+    input: &Path,
+    program: &'ctx Program,
+    types: &'ctx TypeTable,
+    machine: Option<&TargetMachine>,
+    options: CodegenOptions,
+) -> Module<'ctx> {
+    let name = input.file_stem().map_or_else(
+        || "main".to_owned(),
+        |stem| stem.to_string_lossy().into_owned(),
+    );
+
+    match codegen::compile(context, &name, file, program, types, machine, options) {
+        Ok(module) => module,
         Err(errors) => {
-            eprintln!("{:#?}", errors);
+            report(file, errors.iter().map(Diagnostic::from));
             process::exit(1);
         }
-    };
+    }
+}
 
-    if opts.parse {
-        println!("{:#?}", program);
-        return;
+fn create_target_machine(triple: Option<&str>, level: OptimizationLevel) -> TargetMachine {
+    match target::create_target_machine(triple, level) {
+        Ok(machine) => machine,
+        Err(err) => {
+            eprintln!("Failed to create the target machine: {}", err);
+            process::exit(1);
+        }
     }
+}
+
+fn optimize_module(module: &Module, opts: &OptimizeOpts) {
+    let passes = optimize::pipeline(opts.opt_level);
+
+    if opts.print_passes {
+        for pass in &passes {
+            eprintln!("{}", pass);
+        }
+    }
+
+    if opts.time_passes {
+        let timings = optimize::optimize_timed(module, &passes);
 
-    // evaluate_program(&program);
+        // The time of each pass is measured on its own, so they don't add up to the total.
+        for (pass, duration) in timings.passes {
+            eprintln!(
+                "{:>10.3}ms  {} (approx.)",
+                duration.as_secs_f64() * 1000.0,
+                pass
+            );
+        }
+        eprintln!("{:>10.3}ms  total", timings.total.as_secs_f64() * 1000.0);
+    } else {
+        optimize::optimize(module, &passes);
+    }
+}
+
+fn report(file: &SourceFile, diagnostics: impl Iterator<Item = Diagnostic>) {
+    // Colors are only rendered in a terminal, so that they don't end up in redirected output.
+    let color = io::stderr().is_terminal();
+
+    for diagnostic in diagnostics {
+        eprintln!("{}\n", diagnostic.render(file, color));
+    }
 }
@@ -11,7 +11,13 @@ pub enum ParsingErrorKind {
     ReservedKeyword(String),
     InvalidInteger(String),
     InvalidFloat(String),
+    InvalidEscape(String),
+    InvalidChar(String),
     TopLevelReturn,
+    TopLevelVariable,
+    TopLevelExpression,
+    TopLevelStatement,
+    InvalidAssignmentTarget,
 }
 
 impl fmt::Display for ParsingErrorKind {
@@ -31,9 +37,43 @@ impl fmt::Display for ParsingErrorKind {
             InvalidFloat(value) => {
                 write!(f, "Invalid float literal. Failed to parse '{}'.", value)
             }
+            InvalidEscape(value) => {
+                write!(f, "Invalid escape sequence '{}'.", value)
+            }
+            InvalidChar(value) => {
+                write!(
+                    f,
+                    "Invalid char literal. '{}' is not an ASCII character.",
+                    value
+                )
+            }
             TopLevelReturn => {
                 write!(f, "Invalid return statement. Top level code can't return.")
             }
+            TopLevelVariable => {
+                write!(
+                    f,
+                    "Invalid variable declaration. Variables can only be declared inside of a function."
+                )
+            }
+            TopLevelExpression => {
+                write!(
+                    f,
+                    "Invalid expression. Expressions can only be used inside of a function."
+                )
+            }
+            TopLevelStatement => {
+                write!(
+                    f,
+                    "Invalid statement. Statements can only be used inside of a function."
+                )
+            }
+            InvalidAssignmentTarget => {
+                write!(
+                    f,
+                    "Invalid assignment. The left-hand side must be a variable, or a field or an index expression on a variable."
+                )
+            }
         }
     }
 }
@@ -100,43 +140,76 @@ fn format_rules(rules: Vec<Rule>) -> String {
             match rule {
                 Rule::and => "&&",
                 Rule::arguments => "arguments",
+                Rule::array_literal => "array literal",
+                Rule::array_type => "array type",
+                Rule::assignment => "assignment",
+                Rule::assignment_operator => "assignment operator",
                 Rule::bang => "!",
                 Rule::bang_equal => "!=",
                 Rule::binary => "binary expression",
                 Rule::binary_operator => "binary operator",
                 Rule::block => "block",
                 Rule::boolean => "boolean",
+                Rule::break_statement => "break statement",
                 Rule::call => "function call",
+                Rule::char | Rule::char_text => "char",
+                Rule::continue_statement => "continue statement",
                 Rule::EOI => "end of file",
                 Rule::equal => "=",
+                Rule::escape => "escape sequence",
                 Rule::equal_equal => "==",
                 Rule::expression => "expression",
                 Rule::expression_statement => "expression statement",
+                Rule::field_access => "field access",
+                Rule::field_declaration => "field declaration",
+                Rule::field_initializer => "field initializer",
                 Rule::float | Rule::float_characteristic | Rule::float_mantissa => "float",
                 Rule::function_declaration => "function declaration",
                 Rule::greater => ">",
                 Rule::greater_equal => ">=",
                 Rule::identifier => "identifier",
+                Rule::if_expression => "if expression",
+                Rule::if_statement => "if statement",
+                Rule::index => "index",
                 Rule::integer => "integer",
+                Rule::invalid_statement => "statement",
+                Rule::invalid_top_level_decl => "top level declaration",
+                Rule::label => "loop label",
                 Rule::less => "<",
                 Rule::less_equal => "<=",
                 Rule::logical => "logical expression",
                 Rule::logical_operator => "logical operator",
+                Rule::loop_statement => "loop statement",
                 Rule::minus => "-",
+                Rule::misplaced_statement => "statement",
+                Rule::minus_equal => "-=",
                 Rule::or => "||",
                 Rule::parameters => "function parameters",
                 Rule::plus => "+",
+                Rule::plus_equal => "+=",
                 Rule::primary => "primary expression",
                 Rule::program => "program",
                 Rule::return_statement => "return statement",
+                Rule::skipped_input => "statement",
                 Rule::slash => "/",
+                Rule::slash_equal => "/=",
+                Rule::slice => "slice",
+                Rule::slice_start | Rule::slice_end => "slice bound",
+                Rule::slice_type => "slice type",
                 Rule::star => "*",
+                Rule::star_equal => "*=",
                 Rule::statement => "statement",
+                Rule::string | Rule::string_text => "string",
+                Rule::struct_declaration => "struct declaration",
+                Rule::struct_literal => "struct literal",
                 Rule::top_level_decl => "top level declaration",
                 Rule::ty => "type",
                 Rule::unary => "unary expression",
                 Rule::unary_operator => "unary operator",
+                Rule::valid_statement => "statement",
+                Rule::valid_top_level_decl => "top level declaration",
                 Rule::variable_declaration => "variable declaration",
+                Rule::while_statement => "while statement",
                 Rule::WHITESPACE => "whitespace character",
             }
             .to_owned()
@@ -7,65 +7,97 @@ use pest::{
 use crate::ast::{
     ast::{
         BinaryOp, Block, Decl, DeclKind, Expr, ExprKind, Ident, Lit, LitKind, LogicalOp, Program,
-        Stmt, StmtKind, TopLevelDecl, TopLevelDeclKind, Ty, UnaryOp,
+        Stmt, StmtKind, TopLevelDecl, TopLevelDeclKind, Ty, TyKind, UnaryOp,
     },
-    location::Location,
+    location::{Location, SourceFile},
 };
 
 pub mod error;
 mod grammar;
 
 use self::{
-    error::{ParsingError, ParsingErrorKind},
+    error::{ParsingError, ParsingErrorKind, ParsingErrorLocation},
     grammar::{Grammar, Rule},
 };
 
-struct ParsingCtx {
+struct ParsingCtx<'a> {
+    file: &'a SourceFile,
     errors: Vec<ParsingError>,
 }
 
-impl ParsingCtx {
-    fn new() -> Self {
-        ParsingCtx { errors: Vec::new() }
+impl<'a> ParsingCtx<'a> {
+    fn new(file: &'a SourceFile) -> Self {
+        ParsingCtx {
+            file,
+            errors: Vec::new(),
+        }
     }
 
-    fn is_failed(&self) -> bool {
-        !self.errors.is_empty()
+    fn location(&self, pair: &Pair<Rule>) -> Location {
+        let span = pair.as_span();
+        Location::new(self.file.id(), span.start(), span.end())
+    }
+
+    /// Returns the syntax error of the input skipped by the `invalid_statement` and
+    /// `invalid_top_level_decl` rules. The input is parsed again from the start of the skipped
+    /// input with the `rule` that failed, to retrieve the error reported by pest.
+    fn syntax_error(&self, rule: Rule, offset: usize) -> ParsingError {
+        let err = match Grammar::parse(rule, &self.file.src()[offset..]) {
+            Err(err) => ParsingError::from(err),
+            Ok(_) => unreachable!("Skipped input at {} is valid", offset),
+        };
+
+        let location = match err.location {
+            ParsingErrorLocation::Position(pos) => ParsingErrorLocation::Position(offset + pos),
+            ParsingErrorLocation::Span((start, end)) => {
+                ParsingErrorLocation::Span((offset + start, offset + end))
+            }
+        };
+
+        ParsingError { location, ..err }
     }
 }
 
-pub fn parse(input: &str) -> Result<Program, Vec<ParsingError>> {
-    match Grammar::parse(Rule::program, input) {
+/// Parse a source file. Syntax errors in statements don't stop the parsing: the erroneous
+/// statements are skipped and a partial program is returned along with all the errors. The
+/// program is only missing when the file structure itself can't be parsed.
+pub fn parse(file: &SourceFile) -> (Option<Program>, Vec<ParsingError>) {
+    match Grammar::parse(Rule::program, file.src()) {
         Ok(pairs) => {
             let start = 0;
             let mut end = 0;
 
-            let mut ctx = ParsingCtx::new();
+            let mut ctx = ParsingCtx::new(file);
             let mut decls = Vec::new();
 
             for pair in pairs {
                 match pair.as_rule() {
                     Rule::EOI => end = pair.as_span().end(),
-                    Rule::function_declaration => {
+                    Rule::function_declaration | Rule::struct_declaration => {
                         match parse_top_level_decl(&mut ctx, pair) {
                             Ok(decl) => decls.push(decl),
                             Err(err) => ctx.errors.push(err),
                         };
                     }
+                    Rule::misplaced_statement => {
+                        let err = misplaced_stmt_error(&ctx, pair);
+                        ctx.errors.push(err);
+                    }
+                    Rule::invalid_top_level_decl => {
+                        skip_invalid_input(&mut ctx, pair, Rule::valid_top_level_decl);
+                    }
                     _ => unreachable!("Unexpected top level declaration {:?}", pair),
                 };
             }
 
-            if ctx.is_failed() {
-                Err(ctx.errors)
-            } else {
-                Ok(Program {
-                    decls,
-                    location: Location::new(start, end),
-                })
-            }
+            let program = Program {
+                decls,
+                location: Location::new(file.id(), start, end),
+            };
+
+            (Some(program), ctx.errors)
         }
-        Err(err) => Err(vec![ParsingError::from(err)]),
+        Err(err) => (None, vec![ParsingError::from(err)]),
     }
 }
 
@@ -73,48 +105,98 @@ fn parse_top_level_decl(
     ctx: &mut ParsingCtx,
     pair: Pair<Rule>,
 ) -> Result<TopLevelDecl, ParsingError> {
-    let location = Location::from(&pair);
+    let location = ctx.location(&pair);
 
     match pair.as_rule() {
         Rule::function_declaration => {
             let mut inner = pair.into_inner();
 
-            let ident = parse_ident(inner.next().unwrap())?;
+            let ident = parse_ident(ctx, inner.next().unwrap())?;
 
             let mut params = Vec::new();
             let mut parameter_pairs = inner.next().unwrap().into_inner();
             while let (Some(name), Some(ty)) = (parameter_pairs.next(), parameter_pairs.next()) {
-                params.push((parse_ident(name)?, parse_ty(ty)?));
+                params.push((parse_ident(ctx, name)?, parse_ty(ctx, ty)?));
             }
 
-            let return_ty = parse_ty(inner.next().unwrap())?;
+            // Functions without return type return `void`.
+            let mut next = inner.next().unwrap();
+            let return_ty = match next.as_rule() {
+                Rule::ty => {
+                    let ty = parse_ty(ctx, next)?;
+                    next = inner.next().unwrap();
+                    Some(ty)
+                }
+                _ => None,
+            };
 
-            let body = parse_block(ctx, inner.next().unwrap());
+            let body = parse_block(ctx, next);
 
             Ok(TopLevelDecl {
                 kind: TopLevelDeclKind::Fn(ident, params, return_ty, body),
                 location,
             })
         }
+        Rule::struct_declaration => {
+            let mut inner = pair.into_inner();
+
+            let ident = parse_ident(ctx, inner.next().unwrap())?;
+
+            let mut fields = Vec::new();
+            for field_pair in inner {
+                let mut field_inner = field_pair.into_inner();
+                let name = parse_ident(ctx, field_inner.next().unwrap())?;
+                let ty = parse_ty(ctx, field_inner.next().unwrap())?;
+                fields.push((name, ty));
+            }
+
+            Ok(TopLevelDecl {
+                kind: TopLevelDeclKind::Struct(ident, fields),
+                location,
+            })
+        }
         _ => unreachable!("Unexpected top level declaration {:?}", pair),
     }
 }
 
+fn misplaced_stmt_error(ctx: &ParsingCtx, pair: Pair<Rule>) -> ParsingError {
+    let stmt = pair.into_inner().next().unwrap();
+
+    let kind = match stmt.as_rule() {
+        Rule::return_statement => ParsingErrorKind::TopLevelReturn,
+        Rule::variable_declaration => ParsingErrorKind::TopLevelVariable,
+        Rule::expression_statement => ParsingErrorKind::TopLevelExpression,
+        _ => ParsingErrorKind::TopLevelStatement,
+    };
+
+    // Statements ending with an optional part, like `if` without `else`, include the whitespace
+    // following them in their span.
+    let mut location = ctx.location(&stmt);
+    location.end = location.start + stmt.as_str().trim_end().len();
+
+    ParsingError::new(kind, location)
+}
+
 fn parse_stmt(ctx: &mut ParsingCtx, pair: Pair<Rule>) -> Option<Stmt> {
+    if pair.as_rule() == Rule::invalid_statement {
+        skip_invalid_input(ctx, pair, Rule::valid_statement);
+        return None;
+    }
+
     let parse_stmt_inner = || {
-        let location = Location::from(&pair);
+        let location = ctx.location(&pair);
 
         match pair.as_rule() {
             Rule::variable_declaration => {
                 let mut inner = pair.into_inner();
 
-                let ident = parse_ident(inner.next().unwrap())?;
+                let ident = parse_ident(ctx, inner.next().unwrap())?;
                 let mut ty: Option<Ty> = None;
                 let mut init: Option<Expr> = None;
 
                 for inner_pair in inner {
                     match inner_pair.as_rule() {
-                        Rule::ty => ty = Some(parse_ty(inner_pair)?),
+                        Rule::ty => ty = Some(parse_ty(ctx, inner_pair)?),
                         Rule::expression => init = Some(parse_expr(ctx, inner_pair)?),
                         _ => {
                             unreachable!("Unexpected variable declaration {:?}", inner_pair)
@@ -143,6 +225,74 @@ fn parse_stmt(ctx: &mut ParsingCtx, pair: Pair<Rule>) -> Option<Stmt> {
                     location,
                 })
             }
+            Rule::if_statement => {
+                let mut inner = pair.into_inner();
+
+                let condition = parse_expr(ctx, inner.next().unwrap())?;
+                let then_block = parse_block(ctx, inner.next().unwrap());
+
+                // Errors in the else branch are already reported by the nested statement.
+                let else_stmt = inner
+                    .next()
+                    .and_then(|pair| parse_stmt(ctx, pair))
+                    .map(Box::new);
+
+                Ok(Stmt {
+                    kind: StmtKind::If(condition, then_block, else_stmt),
+                    location,
+                })
+            }
+            Rule::while_statement => {
+                let mut inner = pair.into_inner().peekable();
+
+                let label = match inner.peek().map(|pair| pair.as_rule()) {
+                    Some(Rule::label) => Some(parse_label(ctx, inner.next().unwrap())?),
+                    _ => None,
+                };
+                let condition = parse_expr(ctx, inner.next().unwrap())?;
+                let body = parse_block(ctx, inner.next().unwrap());
+
+                Ok(Stmt {
+                    kind: StmtKind::While(label, condition, body),
+                    location,
+                })
+            }
+            Rule::loop_statement => {
+                let mut inner = pair.into_inner().peekable();
+
+                let label = match inner.peek().map(|pair| pair.as_rule()) {
+                    Some(Rule::label) => Some(parse_label(ctx, inner.next().unwrap())?),
+                    _ => None,
+                };
+                let body = parse_block(ctx, inner.next().unwrap());
+
+                Ok(Stmt {
+                    kind: StmtKind::Loop(label, body),
+                    location,
+                })
+            }
+            Rule::break_statement => {
+                let label = match pair.into_inner().next() {
+                    Some(label) => Some(parse_label(ctx, label)?),
+                    None => None,
+                };
+
+                Ok(Stmt {
+                    kind: StmtKind::Break(label),
+                    location,
+                })
+            }
+            Rule::continue_statement => {
+                let label = match pair.into_inner().next() {
+                    Some(label) => Some(parse_label(ctx, label)?),
+                    None => None,
+                };
+
+                Ok(Stmt {
+                    kind: StmtKind::Continue(label),
+                    location,
+                })
+            }
             Rule::expression_statement => {
                 let mut inner = pair.into_inner();
 
@@ -174,8 +324,18 @@ fn parse_stmt(ctx: &mut ParsingCtx, pair: Pair<Rule>) -> Option<Stmt> {
     }
 }
 
+fn skip_invalid_input(ctx: &mut ParsingCtx, pair: Pair<Rule>, rule: Rule) {
+    let err = ctx.syntax_error(rule, pair.as_span().start());
+    ctx.errors.push(err);
+
+    // A block ending the skipped input is still parsed, to report the errors it contains.
+    for inner in pair.into_inner() {
+        parse_block(ctx, inner);
+    }
+}
+
 fn parse_block(ctx: &mut ParsingCtx, pair: Pair<Rule>) -> Block {
-    let location = Location::from(&pair);
+    let location = ctx.location(&pair);
 
     match pair.as_rule() {
         Rule::block => {
@@ -217,7 +377,7 @@ lazy_static! {
 }
 
 fn parse_expr(ctx: &mut ParsingCtx, pair: Pair<Rule>) -> Result<Expr, ParsingError> {
-    let location = Location::from(&pair);
+    let location = ctx.location(&pair);
 
     match pair.as_rule() {
         Rule::expression => {
@@ -225,6 +385,28 @@ fn parse_expr(ctx: &mut ParsingCtx, pair: Pair<Rule>) -> Result<Expr, ParsingErr
             parse_expr(ctx, inner)
         }
 
+        Rule::assignment => {
+            let mut inner = pair.into_inner();
+
+            let target = parse_expr(ctx, inner.next().unwrap())?;
+            let op = parse_assignment_op(inner.next().unwrap());
+            let value = parse_expr(ctx, inner.next().unwrap())?;
+
+            if let Some(non_place) = target.non_place() {
+                return Err(ParsingError::new(
+                    ParsingErrorKind::InvalidAssignmentTarget,
+                    non_place.location,
+                ));
+            }
+
+            let kind = match op {
+                Some(op) => ExprKind::AssignOp(op, Box::new(target), Box::new(value)),
+                None => ExprKind::Assign(Box::new(target), Box::new(value)),
+            };
+
+            Ok(Expr { kind, location })
+        }
+
         Rule::logical => {
             let inner = pair.into_inner();
             PREC_LOGICAL_CLIMBER.climb(
@@ -234,7 +416,7 @@ fn parse_expr(ctx: &mut ParsingCtx, pair: Pair<Rule>) -> Result<Expr, ParsingErr
                     let lhs = lhs_res?;
                     let rhs = rhs_res?;
 
-                    let location = Location::new(lhs.location.start(), rhs.location.end());
+                    let location = lhs.location.to(rhs.location);
 
                     Ok(Expr {
                         kind: ExprKind::Logical(parse_logical_op(op), Box::new(lhs), Box::new(rhs)),
@@ -253,7 +435,7 @@ fn parse_expr(ctx: &mut ParsingCtx, pair: Pair<Rule>) -> Result<Expr, ParsingErr
                     let lhs = lhs_res?;
                     let rhs = rhs_res?;
 
-                    let location = Location::new(lhs.location.start(), rhs.location.end());
+                    let location = lhs.location.to(rhs.location);
 
                     Ok(Expr {
                         kind: ExprKind::Binary(parse_binary_op(op), Box::new(lhs), Box::new(rhs)),
@@ -269,11 +451,11 @@ fn parse_expr(ctx: &mut ParsingCtx, pair: Pair<Rule>) -> Result<Expr, ParsingErr
 
             match next.as_rule() {
                 Rule::unary_operator => {
-                    let start = next.as_span().start();
+                    let op_location = ctx.location(&next);
                     let op = parse_unary_op(next);
                     let expression = parse_expr(ctx, inner.next().unwrap())?;
 
-                    let location = Location::new(start, expression.location.end());
+                    let location = op_location.to(expression.location);
 
                     Ok(Expr {
                         kind: ExprKind::Unary(op, Box::new(expression)),
@@ -290,32 +472,108 @@ fn parse_expr(ctx: &mut ParsingCtx, pair: Pair<Rule>) -> Result<Expr, ParsingErr
             let mut expr = parse_expr(ctx, inner.next().unwrap())?;
 
             for pair in inner {
-                let location = Location::new(expr.location.start, pair.as_span().end());
+                let location = expr.location.to(ctx.location(&pair));
+
+                let kind = match pair.as_rule() {
+                    Rule::arguments => {
+                        let mut args = Vec::new();
+                        for inner in pair.into_inner() {
+                            let arg = parse_expr(ctx, inner)?;
+                            args.push(Box::new(arg));
+                        }
 
-                let mut args = Vec::new();
-                for inner in pair.into_inner() {
-                    let arg = parse_expr(ctx, inner)?;
-                    args.push(Box::new(arg));
-                }
+                        ExprKind::Call(Box::new(expr), args)
+                    }
+                    Rule::field_access => {
+                        let field = parse_ident(ctx, pair.into_inner().next().unwrap())?;
+                        ExprKind::Field(Box::new(expr), field)
+                    }
+                    Rule::index => {
+                        let index = parse_expr(ctx, pair.into_inner().next().unwrap())?;
+                        ExprKind::Index(Box::new(expr), Box::new(index))
+                    }
+                    Rule::slice => {
+                        let mut start = None;
+                        let mut end = None;
+                        for inner in pair.into_inner() {
+                            let rule = inner.as_rule();
+                            let bound = parse_expr(ctx, inner.into_inner().next().unwrap())?;
+
+                            match rule {
+                                Rule::slice_start => start = Some(Box::new(bound)),
+                                _ => end = Some(Box::new(bound)),
+                            }
+                        }
 
-                expr = Expr {
-                    kind: ExprKind::Call(Box::new(expr), args),
-                    location,
-                }
+                        ExprKind::Slice(Box::new(expr), start, end)
+                    }
+                    _ => unreachable!("Unexpected postfix expression {:?}", pair),
+                };
+
+                expr = Expr { kind, location }
             }
 
             Ok(expr)
         }
 
+        Rule::if_expression => {
+            let mut inner = pair.into_inner();
+
+            let condition = parse_expr(ctx, inner.next().unwrap())?;
+            let then_expr = parse_expr(ctx, inner.next().unwrap())?;
+            let else_expr = parse_expr(ctx, inner.next().unwrap())?;
+
+            Ok(Expr {
+                kind: ExprKind::If(
+                    Box::new(condition),
+                    Box::new(then_expr),
+                    Box::new(else_expr),
+                ),
+                location,
+            })
+        }
+
+        Rule::struct_literal => {
+            let mut inner = pair.into_inner();
+
+            let ident = parse_ident(ctx, inner.next().unwrap())?;
+
+            let mut fields = Vec::new();
+            for field_pair in inner {
+                let mut field_inner = field_pair.into_inner();
+                let name = parse_ident(ctx, field_inner.next().unwrap())?;
+                let value = parse_expr(ctx, field_inner.next().unwrap())?;
+                fields.push((name, Box::new(value)));
+            }
+
+            Ok(Expr {
+                kind: ExprKind::Struct(ident, fields),
+                location,
+            })
+        }
+
+        Rule::array_literal => {
+            let mut elements = Vec::new();
+            for inner in pair.into_inner() {
+                let element = parse_expr(ctx, inner)?;
+                elements.push(Box::new(element));
+            }
+
+            Ok(Expr {
+                kind: ExprKind::Array(elements),
+                location,
+            })
+        }
+
         Rule::identifier => {
-            let ident = parse_ident(pair)?;
+            let ident = parse_ident(ctx, pair)?;
             Ok(Expr {
                 kind: ExprKind::Ident(ident),
                 location,
             })
         }
-        Rule::integer | Rule::float | Rule::boolean => {
-            let lit = parse_lit(pair)?;
+        Rule::integer | Rule::float | Rule::boolean | Rule::string | Rule::char => {
+            let lit = parse_lit(ctx, pair)?;
             Ok(Expr {
                 kind: ExprKind::Lit(lit),
                 location,
@@ -339,6 +597,8 @@ fn parse_binary_op(pair: Pair<Rule>) -> BinaryOp {
         Rule::minus => BinaryOp::Subtract,
         Rule::star => BinaryOp::Multiply,
         Rule::slash => BinaryOp::Divide,
+        Rule::equal_equal => BinaryOp::Equal,
+        Rule::bang_equal => BinaryOp::NotEqual,
         Rule::greater => BinaryOp::Greater,
         Rule::greater_equal => BinaryOp::GreaterEqual,
         Rule::less => BinaryOp::Less,
@@ -347,6 +607,20 @@ fn parse_binary_op(pair: Pair<Rule>) -> BinaryOp {
     }
 }
 
+/// Returns the binary operator applied by a compound assignment, or `None` for a plain `=`.
+fn parse_assignment_op(pair: Pair<Rule>) -> Option<BinaryOp> {
+    let inner = pair.into_inner().next().unwrap();
+
+    match inner.as_rule() {
+        Rule::equal => None,
+        Rule::plus_equal => Some(BinaryOp::Add),
+        Rule::minus_equal => Some(BinaryOp::Subtract),
+        Rule::star_equal => Some(BinaryOp::Multiply),
+        Rule::slash_equal => Some(BinaryOp::Divide),
+        _ => unreachable!("Invalid assignment operator {:?}", inner),
+    }
+}
+
 fn parse_unary_op(pair: Pair<Rule>) -> UnaryOp {
     let inner = pair.into_inner().next().unwrap();
 
@@ -357,11 +631,11 @@ fn parse_unary_op(pair: Pair<Rule>) -> UnaryOp {
     }
 }
 
-fn parse_ident(pair: Pair<Rule>) -> Result<Ident, ParsingError> {
+fn parse_ident(ctx: &ParsingCtx, pair: Pair<Rule>) -> Result<Ident, ParsingError> {
     match pair.as_rule() {
         Rule::identifier => {
             let name = pair.as_str().to_owned();
-            let location = Location::from(&pair);
+            let location = ctx.location(&pair);
 
             if is_reserved(&name) {
                 Err(ParsingError::new(
@@ -376,11 +650,27 @@ fn parse_ident(pair: Pair<Rule>) -> Result<Ident, ParsingError> {
     }
 }
 
-fn parse_ty(pair: Pair<Rule>) -> Result<Ty, ParsingError> {
+fn parse_label(ctx: &ParsingCtx, pair: Pair<Rule>) -> Result<Ident, ParsingError> {
     match pair.as_rule() {
-        Rule::ty => {
+        Rule::label => {
+            let location = ctx.location(&pair);
+            let ident = parse_ident(ctx, pair.into_inner().next().unwrap())?;
+
+            Ok(Ident {
+                name: ident.name,
+                location,
+            })
+        }
+        _ => unreachable!("Unexpected label {:?}", pair),
+    }
+}
+
+fn parse_ty(ctx: &ParsingCtx, pair: Pair<Rule>) -> Result<Ty, ParsingError> {
+    match pair.as_rule() {
+        Rule::ty => parse_ty(ctx, pair.into_inner().next().unwrap()),
+        Rule::identifier => {
             let name = pair.as_str().to_owned();
-            let location = Location::from(&pair);
+            let location = ctx.location(&pair);
 
             if is_reserved(&name) {
                 Err(ParsingError::new(
@@ -388,15 +678,46 @@ fn parse_ty(pair: Pair<Rule>) -> Result<Ty, ParsingError> {
                     location,
                 ))
             } else {
-                Ok(Ty { name, location })
+                Ok(Ty {
+                    kind: TyKind::Named(name),
+                    location,
+                })
             }
         }
+        Rule::array_type => {
+            let location = ctx.location(&pair);
+            let mut inner = pair.into_inner();
+
+            let element_ty = parse_ty(ctx, inner.next().unwrap())?;
+
+            let len_pair = inner.next().unwrap();
+            let len: u64 = len_pair.as_str().parse().map_err(|_| {
+                ParsingError::new(
+                    ParsingErrorKind::InvalidInteger(len_pair.to_string()),
+                    ctx.location(&len_pair),
+                )
+            })?;
+
+            Ok(Ty {
+                kind: TyKind::Array(Box::new(element_ty), len),
+                location,
+            })
+        }
+        Rule::slice_type => {
+            let location = ctx.location(&pair);
+            let element_ty = parse_ty(ctx, pair.into_inner().next().unwrap())?;
+
+            Ok(Ty {
+                kind: TyKind::Slice(Box::new(element_ty)),
+                location,
+            })
+        }
         _ => unreachable!("Unexpected type {:?}", pair),
     }
 }
 
-fn parse_lit(pair: Pair<Rule>) -> Result<Lit, ParsingError> {
-    let location = Location::from(&pair);
+fn parse_lit(ctx: &ParsingCtx, pair: Pair<Rule>) -> Result<Lit, ParsingError> {
+    let location = ctx.location(&pair);
 
     let kind = match pair.as_rule() {
         Rule::boolean => match pair.as_str() {
@@ -418,22 +739,81 @@ fn parse_lit(pair: Pair<Rule>) -> Result<Lit, ParsingError> {
 
             LitKind::Float(value)
         }
+        Rule::string => {
+            let mut value = String::new();
+            for inner in pair.into_inner() {
+                match inner.as_rule() {
+                    Rule::string_text => value.push_str(inner.as_str()),
+                    _ => value.push(parse_escape(ctx, inner)?),
+                }
+            }
+
+            LitKind::Str(value)
+        }
+        Rule::char => {
+            let inner = pair.into_inner().next().unwrap();
+            let value = match inner.as_rule() {
+                Rule::char_text => inner.as_str().chars().next().unwrap(),
+                _ => parse_escape(ctx, inner.clone())?,
+            };
+
+            // Chars are single bytes, like the elements of a string.
+            if !value.is_ascii() {
+                return Err(ParsingError::new(
+                    ParsingErrorKind::InvalidChar(inner.as_str().to_owned()),
+                    location,
+                ));
+            }
+
+            LitKind::Char(value as u8)
+        }
         _ => unreachable!("Unexpected literal value {:?}", pair),
     };
 
     Ok(Lit { kind, location })
 }
 
+fn parse_escape(ctx: &ParsingCtx, pair: Pair<Rule>) -> Result<char, ParsingError> {
+    let escape = pair.as_str();
+
+    let value = match escape {
+        "\\n" => Some('\n'),
+        "\\r" => Some('\r'),
+        "\\t" => Some('\t'),
+        "\\0" => Some('\0'),
+        "\\\\" => Some('\\'),
+        "\\\"" => Some('"'),
+        "\\'" => Some('\''),
+        // Hexadecimal escapes are limited to ASCII, so that strings remain valid UTF-8.
+        _ => escape
+            .strip_prefix("\\x")
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            .filter(u8::is_ascii)
+            .map(char::from),
+    };
+
+    value.ok_or_else(|| {
+        ParsingError::new(
+            ParsingErrorKind::InvalidEscape(escape.to_owned()),
+            ctx.location(&pair),
+        )
+    })
+}
+
 fn is_reserved(name: &str) -> bool {
     matches!(
         name,
-        "class" | "else" | "false" | "fn" | "let" | "if" | "true"
+        "break"
+            | "class"
+            | "continue"
+            | "else"
+            | "false"
+            | "fn"
+            | "let"
+            | "if"
+            | "loop"
+            | "struct"
+            | "true"
+            | "while"
     )
 }
-
-impl<'a> From<&Pair<'a, Rule>> for Location {
-    fn from(pair: &Pair<'a, Rule>) -> Self {
-        let span = pair.as_span();
-        Location::new(span.start(), span.end())
-    }
-}
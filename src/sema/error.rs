@@ -0,0 +1,270 @@
+use std::fmt;
+
+use super::ty::Type;
+use crate::ast::location::Location;
+
+#[derive(Debug)]
+pub enum SemaErrorKind {
+    UnknownType(String),
+    UnknownVariable(String),
+    UnassignedVariable(String),
+    UnknownFunction(String),
+    DuplicateFunction(String),
+    ReservedFunction(String),
+    DuplicateType(String),
+    DuplicateField(String),
+    RecursiveStruct(String),
+    UnknownField {
+        ty: Type,
+        field: String,
+    },
+    MissingFields {
+        ty: Type,
+        fields: Vec<String>,
+    },
+    InvalidFieldAccess(Type),
+    InvalidIndex(Type),
+    InvalidIndexType(Type),
+    InvalidSlice(Type),
+    EscapingSlice(Type),
+    InvalidLenArgument(Type),
+    ImmutableString,
+    UnknownExprType,
+    InvalidCallee,
+    InvalidArgumentCount {
+        name: String,
+        expected: usize,
+        actual: usize,
+    },
+    MismatchedTypes {
+        expected: Type,
+        actual: Type,
+    },
+    InvalidBinaryOperands {
+        op: String,
+        lhs: Type,
+        rhs: Type,
+    },
+    InvalidUnaryOperand {
+        op: String,
+        ty: Type,
+    },
+    InvalidVoidType,
+    IntLiteralOutOfRange {
+        value: i128,
+        ty: Type,
+    },
+    MissingReturnValue(Type),
+    UnexpectedReturnValue,
+    MissingReturn(String),
+    UnreachableCode,
+    MissingTypeAnnotation(String),
+    OutsideOfLoop(String),
+    UnknownLabel(String),
+}
+
+impl fmt::Display for SemaErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use SemaErrorKind::*;
+
+        match self {
+            UnknownType(name) => {
+                write!(f, "Unknown type '{}'.", name)
+            }
+            UnknownVariable(name) => {
+                write!(f, "Unknown variable '{}'.", name)
+            }
+            UnassignedVariable(name) => {
+                write!(
+                    f,
+                    "Unassigned variable '{}'. It is used before being assigned on every path.",
+                    name
+                )
+            }
+            UnknownFunction(name) => {
+                write!(f, "Unknown function '{}'.", name)
+            }
+            DuplicateFunction(name) => {
+                write!(f, "Duplicate function. '{}' is already declared.", name)
+            }
+            ReservedFunction(name) => {
+                write!(
+                    f,
+                    "Reserved function name. '{}' is used by the runtime.",
+                    name
+                )
+            }
+            DuplicateType(name) => {
+                write!(f, "Duplicate type. '{}' is already declared.", name)
+            }
+            DuplicateField(name) => {
+                write!(f, "Duplicate field '{}'.", name)
+            }
+            RecursiveStruct(name) => {
+                write!(f, "Recursive struct. '{}' can't contain itself.", name)
+            }
+            UnknownField { ty, field } => {
+                write!(f, "Unknown field '{}' on type '{}'.", field, ty)
+            }
+            MissingFields { ty, fields } => {
+                let fields = fields
+                    .iter()
+                    .map(|field| format!("'{}'", field))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(f, "Missing field(s) {} in initializer of '{}'.", fields, ty)
+            }
+            InvalidFieldAccess(ty) => {
+                write!(f, "Invalid field access. '{}' has no fields.", ty)
+            }
+            InvalidIndex(ty) => {
+                write!(f, "Invalid index. '{}' can't be indexed.", ty)
+            }
+            InvalidIndexType(ty) => {
+                write!(f, "Invalid index. Expected an integer but found '{}'.", ty)
+            }
+            InvalidSlice(ty) => {
+                write!(f, "Invalid slice. '{}' can't be sliced.", ty)
+            }
+            EscapingSlice(ty) => {
+                write!(
+                    f,
+                    "Invalid type '{}'. Slices can't be returned or stored in a struct, as they \
+                     could outlive their array.",
+                    ty
+                )
+            }
+            InvalidLenArgument(ty) => {
+                write!(
+                    f,
+                    "Invalid function call. 'len' expects an array, a slice or a string but found \
+                     '{}'.",
+                    ty
+                )
+            }
+            ImmutableString => {
+                write!(
+                    f,
+                    "Invalid assignment. Strings are immutable, their bytes can't be assigned."
+                )
+            }
+            UnknownExprType => {
+                write!(
+                    f,
+                    "Unknown type. The type of the expression must be known at this point."
+                )
+            }
+            InvalidCallee => {
+                write!(f, "Invalid function call. Only functions can be called.")
+            }
+            InvalidArgumentCount {
+                name,
+                expected,
+                actual,
+            } => {
+                write!(
+                    f,
+                    "Invalid function call. '{}' expects {} argument(s) but {} were provided.",
+                    name, expected, actual
+                )
+            }
+            MismatchedTypes { expected, actual } => {
+                write!(
+                    f,
+                    "Mismatched types. Expected '{}' but found '{}'.",
+                    expected, actual
+                )
+            }
+            InvalidBinaryOperands { op, lhs, rhs } => {
+                write!(
+                    f,
+                    "Invalid operands. '{}' can't be applied to '{}' and '{}'.",
+                    op, lhs, rhs
+                )
+            }
+            InvalidUnaryOperand { op, ty } => {
+                write!(f, "Invalid operand. '{}' can't be applied to '{}'.", op, ty)
+            }
+            InvalidVoidType => {
+                write!(
+                    f,
+                    "Invalid type. 'void' can only be used as the return type of a function."
+                )
+            }
+            IntLiteralOutOfRange { value, ty } => {
+                write!(
+                    f,
+                    "Literal out of range. '{}' doesn't fit in '{}'.",
+                    value, ty
+                )
+            }
+            MissingReturnValue(ty) => {
+                write!(
+                    f,
+                    "Invalid return statement. Expected a value of type '{}'.",
+                    ty
+                )
+            }
+            UnexpectedReturnValue => {
+                write!(
+                    f,
+                    "Invalid return statement. A 'void' function can't return a value."
+                )
+            }
+            MissingReturn(name) => {
+                write!(
+                    f,
+                    "Missing return statement. Not all paths of '{}' return a value.",
+                    name
+                )
+            }
+            UnreachableCode => {
+                write!(f, "Unreachable code. This statement can never be executed.")
+            }
+            MissingTypeAnnotation(name) => {
+                write!(
+                    f,
+                    "Missing type annotation. The type of '{}' can't be determined.",
+                    name
+                )
+            }
+            OutsideOfLoop(keyword) => {
+                write!(
+                    f,
+                    "Invalid {} statement. '{}' can only be used inside of a loop.",
+                    keyword, keyword
+                )
+            }
+            UnknownLabel(name) => {
+                write!(
+                    f,
+                    "Unknown label '{}'. No enclosing loop has this label.",
+                    name
+                )
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SemaError {
+    pub kind: SemaErrorKind,
+    pub location: Location,
+}
+
+impl SemaError {
+    pub fn new(kind: SemaErrorKind, location: Location) -> Self {
+        SemaError { kind, location }
+    }
+
+    /// Warnings are reported without failing the analysis.
+    pub fn is_warning(&self) -> bool {
+        matches!(self.kind, SemaErrorKind::UnreachableCode)
+    }
+}
+
+impl fmt::Display for SemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind)
+    }
+}
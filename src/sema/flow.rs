@@ -0,0 +1,88 @@
+use super::error::{SemaError, SemaErrorKind};
+use crate::ast::ast::{Block, Ident, Stmt, StmtKind};
+
+/// Check the control flow of a function body, warning about the statements that can never be
+/// executed. Returns whether the execution can continue after the body.
+pub fn check_body(body: &Block, errors: &mut Vec<SemaError>) -> bool {
+    check_block(body, false, errors)
+}
+
+/// Check the control flow of a block, `in_loop` telling whether it is nested in a loop. A `break`
+/// or a `continue` outside of a loop is already reported as invalid, so it doesn't make the
+/// following statements unreachable.
+fn check_block(block: &Block, in_loop: bool, errors: &mut Vec<SemaError>) -> bool {
+    let mut falls_through = true;
+
+    for stmt in &block.stmts {
+        // Only the first unreachable statement is reported, the following ones being part of
+        // the same dead code.
+        if !falls_through {
+            errors.push(SemaError::new(
+                SemaErrorKind::UnreachableCode,
+                stmt.location,
+            ));
+            break;
+        }
+
+        falls_through = check_stmt(stmt, in_loop, errors);
+    }
+
+    falls_through
+}
+
+fn check_stmt(stmt: &Stmt, in_loop: bool, errors: &mut Vec<SemaError>) -> bool {
+    match &stmt.kind {
+        StmtKind::Ret(_) => false,
+        StmtKind::Break(_) | StmtKind::Continue(_) => !in_loop,
+        StmtKind::If(_, then_block, else_stmt) => {
+            let then_falls_through = check_block(then_block, in_loop, errors);
+            let else_falls_through = match else_stmt {
+                Some(stmt) => check_stmt(stmt, in_loop, errors),
+                None => true,
+            };
+
+            then_falls_through || else_falls_through
+        }
+        // The condition of a `while` loop is not evaluated, so the loop may never be entered.
+        StmtKind::While(_, _, body) => {
+            check_block(body, true, errors);
+            true
+        }
+        // A `loop` can only be exited with a `break`.
+        StmtKind::Loop(label, body) => {
+            check_block(body, true, errors);
+            breaks_out(&body.stmts, label.as_ref(), false)
+        }
+        StmtKind::Block(block) => check_block(block, in_loop, errors),
+        StmtKind::Decl(_) | StmtKind::Expr(_) => true,
+    }
+}
+
+/// Returns whether the statements contain a `break` exiting the loop with the given label. An
+/// unlabeled `break` only exits the loop when it isn't nested in another loop, and a labeled one
+/// exits the innermost loop with this label.
+fn breaks_out(stmts: &[Stmt], label: Option<&Ident>, is_nested: bool) -> bool {
+    stmts.iter().any(|stmt| match &stmt.kind {
+        StmtKind::Break(None) => !is_nested,
+        StmtKind::Break(Some(target)) => label.map(|label| &label.name) == Some(&target.name),
+        StmtKind::If(_, then_block, else_stmt) => {
+            let else_breaks_out = match else_stmt {
+                Some(stmt) => breaks_out(std::slice::from_ref(stmt), label, is_nested),
+                None => false,
+            };
+
+            breaks_out(&then_block.stmts, label, is_nested) || else_breaks_out
+        }
+        StmtKind::While(nested_label, _, body) | StmtKind::Loop(nested_label, body) => {
+            // A nested loop with the same label shadows the label of the outer loop.
+            let is_shadowed = match (label, nested_label) {
+                (Some(label), Some(nested_label)) => label.name == nested_label.name,
+                _ => false,
+            };
+
+            !is_shadowed && breaks_out(&body.stmts, label, true)
+        }
+        StmtKind::Block(block) => breaks_out(&block.stmts, label, is_nested),
+        _ => false,
+    })
+}
@@ -0,0 +1,197 @@
+use super::ty::Type;
+
+/// Placeholder for a type that is not known yet, resolved by unification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TypeVar(usize);
+
+#[derive(Debug, Clone, Copy)]
+enum Binding {
+    Unbound,
+    Bound(Type),
+    /// Type of an integer literal, any integer type.
+    Int,
+    /// Type of a float literal, any float type.
+    Float,
+    /// Array whose element type is another variable, so that array literals can be inferred
+    /// from the way their elements are used.
+    Array(TypeVar, u64),
+    /// Slice whose element type is another variable.
+    Slice(TypeVar),
+    Link(TypeVar),
+}
+
+impl Binding {
+    fn accepts(self, ty: Type) -> bool {
+        match self {
+            Binding::Int => ty.is_integer(),
+            Binding::Float => ty.is_float(),
+            _ => false,
+        }
+    }
+}
+
+/// Union-find structure holding the type variables of a function body.
+#[derive(Debug, Default)]
+pub struct InferenceTable {
+    bindings: Vec<Binding>,
+}
+
+impl InferenceTable {
+    pub fn fresh(&mut self) -> TypeVar {
+        self.bindings.push(Binding::Unbound);
+        TypeVar(self.bindings.len() - 1)
+    }
+
+    pub fn known(&mut self, ty: Type) -> TypeVar {
+        let binding = match ty {
+            Type::Array((element, len)) => Binding::Array(self.known(*element), *len),
+            Type::Slice(element) => Binding::Slice(self.known(*element)),
+            ty => Binding::Bound(ty),
+        };
+
+        self.bindings.push(binding);
+        TypeVar(self.bindings.len() - 1)
+    }
+
+    /// Type variable of an array of `len` elements of type `element`.
+    pub fn array(&mut self, element: TypeVar, len: u64) -> TypeVar {
+        self.bindings.push(Binding::Array(element, len));
+        TypeVar(self.bindings.len() - 1)
+    }
+
+    /// Type variable of a slice of elements of type `element`.
+    pub fn slice(&mut self, element: TypeVar) -> TypeVar {
+        self.bindings.push(Binding::Slice(element));
+        TypeVar(self.bindings.len() - 1)
+    }
+
+    /// Type variable of an integer literal, which can become any integer type depending on the
+    /// context it is used in.
+    pub fn int_literal(&mut self) -> TypeVar {
+        self.bindings.push(Binding::Int);
+        TypeVar(self.bindings.len() - 1)
+    }
+
+    /// Type variable of a float literal, which can become any float type depending on the
+    /// context it is used in.
+    pub fn float_literal(&mut self) -> TypeVar {
+        self.bindings.push(Binding::Float);
+        TypeVar(self.bindings.len() - 1)
+    }
+
+    /// Returns the type bound to the variable. Literals not constrained by their context are
+    /// only resolved once `apply_defaults` is called.
+    pub fn resolve(&self, var: TypeVar) -> Option<Type> {
+        match self.bindings[self.find(var).0] {
+            Binding::Bound(ty) => Some(ty),
+            Binding::Array(element, len) => self
+                .resolve(element)
+                .map(|element| Type::array_of(element, len)),
+            Binding::Slice(element) => self.resolve(element).map(Type::slice_of),
+            _ => None,
+        }
+    }
+
+    /// Returns whether nothing is known yet about the type of the variable.
+    pub fn is_unbound(&self, var: TypeVar) -> bool {
+        matches!(self.bindings[self.find(var).0], Binding::Unbound)
+    }
+
+    /// Returns the variable of the element type and the length of an array.
+    pub fn as_array(&self, var: TypeVar) -> Option<(TypeVar, u64)> {
+        match self.bindings[self.find(var).0] {
+            Binding::Array(element, len) => Some((element, len)),
+            _ => None,
+        }
+    }
+
+    /// Returns the variable of the element type of a slice.
+    pub fn as_slice(&self, var: TypeVar) -> Option<TypeVar> {
+        match self.bindings[self.find(var).0] {
+            Binding::Slice(element) => Some(element),
+            _ => None,
+        }
+    }
+
+    /// Type used to describe a variable in errors, literals are reported with their default
+    /// type. The variable must not be unbound.
+    pub fn describe(&self, var: TypeVar) -> Type {
+        match self.bindings[self.find(var).0] {
+            Binding::Bound(ty) => ty,
+            Binding::Int => Type::DEFAULT_INT,
+            Binding::Float => Type::DEFAULT_FLOAT,
+            Binding::Array(element, len) => Type::array_of(self.describe(element), len),
+            Binding::Slice(element) => Type::slice_of(self.describe(element)),
+            binding => unreachable!("Binding without type {:?}", binding),
+        }
+    }
+
+    /// Bind the literals that are still unconstrained to their default type.
+    pub fn apply_defaults(&mut self) {
+        for binding in self.bindings.iter_mut() {
+            match binding {
+                Binding::Int => *binding = Binding::Bound(Type::DEFAULT_INT),
+                Binding::Float => *binding = Binding::Bound(Type::DEFAULT_FLOAT),
+                _ => {}
+            }
+        }
+    }
+
+    /// Unify two type variables. When both variables are already bound to different types, the
+    /// conflicting types are returned in the same order as the arguments.
+    pub fn unify(&mut self, a: TypeVar, b: TypeVar) -> Result<(), (Type, Type)> {
+        let a = self.find(a);
+        let b = self.find(b);
+
+        if a == b {
+            return Ok(());
+        }
+
+        let a_binding = self.bindings[a.0];
+        let b_binding = self.bindings[b.0];
+
+        let is_compatible = match (a_binding, b_binding) {
+            (Binding::Unbound, _) => {
+                self.bindings[a.0] = Binding::Link(b);
+                return Ok(());
+            }
+            (_, Binding::Unbound) => {
+                self.bindings[b.0] = Binding::Link(a);
+                return Ok(());
+            }
+            (Binding::Bound(a_ty), Binding::Bound(b_ty)) => a_ty == b_ty,
+            // Arrays are compatible when they have the same length and compatible elements.
+            (Binding::Array(a_element, a_len), Binding::Array(b_element, b_len)) => {
+                a_len == b_len && self.unify(a_element, b_element).is_ok()
+            }
+            (Binding::Slice(a_element), Binding::Slice(b_element)) => {
+                self.unify(a_element, b_element).is_ok()
+            }
+            (Binding::Array(..) | Binding::Slice(_), _)
+            | (_, Binding::Array(..) | Binding::Slice(_)) => false,
+            (Binding::Bound(ty), literal) | (literal, Binding::Bound(ty)) => literal.accepts(ty),
+            (Binding::Int, Binding::Int) | (Binding::Float, Binding::Float) => true,
+            _ => false,
+        };
+
+        if !is_compatible {
+            return Err((self.describe(a), self.describe(b)));
+        }
+
+        // The literal side is linked to the other one, so that a bound type always wins.
+        if let Binding::Bound(_) = a_binding {
+            self.bindings[b.0] = Binding::Link(a);
+        } else {
+            self.bindings[a.0] = Binding::Link(b);
+        }
+
+        Ok(())
+    }
+
+    fn find(&self, var: TypeVar) -> TypeVar {
+        match self.bindings[var.0] {
+            Binding::Link(next) => self.find(next),
+            _ => var,
+        }
+    }
+}
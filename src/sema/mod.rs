@@ -0,0 +1,1320 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::ast::{
+    ast::{
+        BinaryOp, Block, Decl, DeclKind, Expr, ExprKind, Ident, LitKind, LogicalOp, Program, Stmt,
+        StmtKind, TopLevelDeclKind, Ty, TyKind, UnaryOp,
+    },
+    location::Location,
+};
+
+pub mod error;
+mod flow;
+mod infer;
+pub mod ty;
+
+use self::{
+    error::{SemaError, SemaErrorKind},
+    infer::{InferenceTable, TypeVar},
+    ty::{FnSig, StructDef, Type, UIntTy},
+};
+
+/// Functions of the C library called by the generated code, which can't be declared by the
+/// program.
+pub const RUNTIME_FUNCTIONS: [&str; 3] = ["abort", "memcmp", "write"];
+
+/// Types computed by the semantic analysis, consumed by the code generation.
+#[derive(Debug, Default)]
+pub struct TypeTable {
+    exprs: HashMap<Location, Type>,
+    vars: HashMap<Location, Type>,
+    fns: HashMap<String, FnSig>,
+    structs: HashMap<String, StructDef>,
+}
+
+impl TypeTable {
+    pub fn expr_type(&self, expr: &Expr) -> Type {
+        self.exprs[&expr.location]
+    }
+
+    /// Returns the type of a variable or a parameter from the identifier of its declaration.
+    pub fn var_type(&self, ident: &Ident) -> Type {
+        self.vars[&ident.location]
+    }
+
+    /// Returns the types of all the variables and parameters, along with the location of the
+    /// identifier of their declaration.
+    pub fn vars(&self) -> impl Iterator<Item = (Location, Type)> + '_ {
+        self.vars.iter().map(|(location, ty)| (*location, *ty))
+    }
+
+    pub fn fn_sig(&self, name: &str) -> &FnSig {
+        &self.fns[name]
+    }
+
+    pub fn struct_def(&self, name: &str) -> &StructDef {
+        &self.structs[name]
+    }
+}
+
+/// Variable declared in a function body, tracked until its type is inferred.
+struct Local {
+    ident_name: String,
+    location: Location,
+    var: TypeVar,
+    // Variables whose declaration is already invalid aren't reported a second time when their
+    // type can't be inferred.
+    is_valid: bool,
+}
+
+/// Integer literal, checked against the bounds of its type once the type is inferred.
+struct IntLiteral {
+    var: TypeVar,
+    value: i128,
+    location: Location,
+}
+
+enum OperandKind {
+    Unary(String),
+    Binary(String),
+    Index,
+}
+
+/// Validity check of an operand, deferred until the operand type is inferred.
+struct OperandCheck {
+    var: TypeVar,
+    is_valid: fn(&Type) -> bool,
+    kind: OperandKind,
+    location: Location,
+}
+
+impl OperandCheck {
+    fn error(&self, ty: Type) -> SemaError {
+        let kind = match &self.kind {
+            OperandKind::Unary(op) => SemaErrorKind::InvalidUnaryOperand { op: op.clone(), ty },
+            OperandKind::Binary(op) => SemaErrorKind::InvalidBinaryOperands {
+                op: op.clone(),
+                lhs: ty,
+                rhs: ty,
+            },
+            OperandKind::Index => SemaErrorKind::InvalidIndexType(ty),
+        };
+
+        SemaError::new(kind, self.location)
+    }
+}
+
+/// Loop enclosing the statements being checked.
+struct LoopScope {
+    label: Option<String>,
+    /// Variables that are unassigned on some path breaking out of the loop.
+    unassigned_at_break: HashSet<TypeVar>,
+}
+
+struct SemaCtx {
+    errors: Vec<SemaError>,
+    table: TypeTable,
+    scopes: Vec<HashMap<String, TypeVar>>,
+    return_ty: Option<Type>,
+    loops: Vec<LoopScope>,
+    // Variables declared without an initializer that are not assigned on every path leading to
+    // the code being checked. Paths that don't continue, after a `return` for instance, leave it
+    // empty so that they don't constrain the code following a branch.
+    unassigned: HashSet<TypeVar>,
+    infer: InferenceTable,
+    locals: Vec<Local>,
+    exprs: Vec<(Location, TypeVar)>,
+    operand_checks: Vec<OperandCheck>,
+    int_literals: Vec<IntLiteral>,
+}
+
+impl SemaCtx {
+    fn new() -> Self {
+        SemaCtx {
+            errors: Vec::new(),
+            table: TypeTable::default(),
+            scopes: Vec::new(),
+            return_ty: None,
+            loops: Vec::new(),
+            unassigned: HashSet::new(),
+            infer: InferenceTable::default(),
+            locals: Vec::new(),
+            exprs: Vec::new(),
+            operand_checks: Vec::new(),
+            int_literals: Vec::new(),
+        }
+    }
+
+    fn is_failed(&self) -> bool {
+        self.errors.iter().any(|err| !err.is_warning())
+    }
+
+    fn enter_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    fn exit_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn declare_var(&mut self, ident: &Ident, var: TypeVar, is_valid: bool) {
+        self.locals.push(Local {
+            ident_name: ident.name.clone(),
+            location: ident.location,
+            var,
+            is_valid,
+        });
+        self.scopes
+            .last_mut()
+            .expect("Variable declared outside of a scope")
+            .insert(ident.name.clone(), var);
+    }
+
+    /// Mark the variable as invalid after an invalid assignment, so that it isn't reported a
+    /// second time when its type can't be inferred.
+    fn invalidate_var(&mut self, var: TypeVar) {
+        for local in self.locals.iter_mut().filter(|local| local.var == var) {
+            local.is_valid = false;
+        }
+    }
+
+    fn lookup_var(&self, ident: &Ident) -> Result<TypeVar, SemaError> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(&ident.name).copied())
+            .ok_or_else(|| {
+                SemaError::new(
+                    SemaErrorKind::UnknownVariable(ident.name.clone()),
+                    ident.location,
+                )
+            })
+    }
+
+    /// Continue the analysis with the variables unassigned on any of the given paths.
+    fn merge_unassigned(&mut self, paths: Vec<HashSet<TypeVar>>) {
+        self.unassigned = paths.into_iter().flatten().collect();
+    }
+
+    fn check_operand(&mut self, check: OperandCheck) -> Result<(), SemaError> {
+        match self.infer.resolve(check.var) {
+            Some(ty) if !(check.is_valid)(&ty) => Err(check.error(ty)),
+            Some(_) => Ok(()),
+            None => {
+                self.operand_checks.push(check);
+                Ok(())
+            }
+        }
+    }
+
+    /// Resolve the types inferred for the current function body into the type table, and report
+    /// the variables whose type couldn't be determined. Literals that are not constrained by their
+    /// context fall back to their default type.
+    fn resolve_fn(&mut self) {
+        let mut infer = std::mem::take(&mut self.infer);
+        infer.apply_defaults();
+
+        for (location, var) in self.exprs.drain(..) {
+            if let Some(ty) = infer.resolve(var) {
+                self.table.exprs.insert(location, ty);
+            }
+        }
+
+        for local in self.locals.drain(..) {
+            match infer.resolve(local.var) {
+                Some(ty) => {
+                    self.table.vars.insert(local.location, ty);
+                }
+                None if local.is_valid => self.errors.push(SemaError::new(
+                    SemaErrorKind::MissingTypeAnnotation(local.ident_name),
+                    local.location,
+                )),
+                None => {}
+            }
+        }
+
+        for check in self.operand_checks.drain(..) {
+            if let Some(ty) = infer.resolve(check.var) {
+                if !(check.is_valid)(&ty) {
+                    self.errors.push(check.error(ty));
+                }
+            }
+        }
+
+        for literal in self.int_literals.drain(..) {
+            let ty = match infer.resolve(literal.var) {
+                Some(ty) if ty.is_integer() => ty,
+                _ => continue,
+            };
+
+            // Negated unsigned literals are already reported as invalid operands.
+            if literal.value < 0 && !ty.is_signed() {
+                continue;
+            }
+
+            if !ty.contains_int(literal.value) {
+                self.errors.push(SemaError::new(
+                    SemaErrorKind::IntLiteralOutOfRange {
+                        value: literal.value,
+                        ty,
+                    },
+                    literal.location,
+                ));
+            }
+        }
+    }
+}
+
+/// Check the program, returning the types computed for the code generation along with the
+/// reported errors and warnings. The types are only returned when no error is reported.
+pub fn check(program: &Program) -> (Option<TypeTable>, Vec<SemaError>) {
+    let mut ctx = SemaCtx::new();
+
+    // The structs are declared before their fields are resolved, so that fields and signatures
+    // can refer to any struct regardless of where it is declared in the file.
+    for decl in &program.decls {
+        if let TopLevelDeclKind::Struct(ident, _) = &decl.kind {
+            if let Err(err) = declare_struct(&mut ctx, ident) {
+                ctx.errors.push(err);
+            }
+        }
+    }
+
+    for decl in &program.decls {
+        if let TopLevelDeclKind::Struct(ident, fields) = &decl.kind {
+            define_struct(&mut ctx, ident, fields);
+        }
+    }
+
+    check_recursive_structs(&mut ctx, program);
+
+    // All the signatures are resolved upfront, so that a function body can call any function
+    // regardless of where it is declared in the file.
+    for decl in &program.decls {
+        if let TopLevelDeclKind::Fn(ident, params, return_ty, _) = &decl.kind {
+            if let Err(err) = declare_fn(&mut ctx, ident, params, return_ty.as_ref()) {
+                ctx.errors.push(err);
+            }
+        }
+    }
+
+    for decl in &program.decls {
+        if let TopLevelDeclKind::Fn(ident, params, _, body) = &decl.kind {
+            check_fn(&mut ctx, ident, params, body);
+        }
+    }
+
+    if ctx.is_failed() {
+        (None, ctx.errors)
+    } else {
+        (Some(ctx.table), ctx.errors)
+    }
+}
+
+fn declare_struct(ctx: &mut SemaCtx, ident: &Ident) -> Result<(), SemaError> {
+    if Type::from_name(&ident.name).is_some() || ctx.table.structs.contains_key(&ident.name) {
+        return Err(SemaError::new(
+            SemaErrorKind::DuplicateType(ident.name.clone()),
+            ident.location,
+        ));
+    }
+
+    ctx.table.structs.insert(
+        ident.name.clone(),
+        StructDef {
+            name: ident.name.clone(),
+            fields: Vec::new(),
+            location: ident.location,
+        },
+    );
+
+    Ok(())
+}
+
+fn define_struct(ctx: &mut SemaCtx, ident: &Ident, fields: &[(Ident, Ty)]) {
+    // Duplicate declarations have already been reported when declared.
+    match ctx.table.structs.get(&ident.name) {
+        Some(def) if def.location == ident.location => {}
+        _ => return,
+    }
+
+    let mut resolved_fields: Vec<(String, Type)> = Vec::new();
+    for (field, ty) in fields {
+        if resolved_fields.iter().any(|(name, _)| *name == field.name) {
+            ctx.errors.push(SemaError::new(
+                SemaErrorKind::DuplicateField(field.name.clone()),
+                field.location,
+            ));
+            continue;
+        }
+
+        match resolve_value_ty(ctx, ty).and_then(|resolved| check_not_slice(resolved, ty)) {
+            Ok(ty) => resolved_fields.push((field.name.clone(), ty)),
+            Err(err) => ctx.errors.push(err),
+        }
+    }
+
+    ctx.table.structs.get_mut(&ident.name).unwrap().fields = resolved_fields;
+}
+
+/// Report the structs containing themselves through their fields, which would have an infinite
+/// size.
+fn check_recursive_structs(ctx: &mut SemaCtx, program: &Program) {
+    fn contains(
+        table: &TypeTable,
+        ty: Type,
+        target: &str,
+        visited: &mut HashSet<&'static str>,
+    ) -> bool {
+        match ty {
+            Type::Struct(name) if name == target => true,
+            Type::Struct(name) if visited.insert(name) => table.structs[name]
+                .fields
+                .iter()
+                .any(|(_, ty)| contains(table, *ty, target, visited)),
+            Type::Array((element, _)) => contains(table, *element, target, visited),
+            _ => false,
+        }
+    }
+
+    for decl in &program.decls {
+        if let TopLevelDeclKind::Struct(ident, _) = &decl.kind {
+            // Duplicate declarations have already been reported when declared.
+            let def = match ctx.table.structs.get(&ident.name) {
+                Some(def) if def.location == ident.location => def,
+                _ => continue,
+            };
+
+            let mut visited = HashSet::new();
+            let is_recursive = def
+                .fields
+                .iter()
+                .any(|(_, ty)| contains(&ctx.table, *ty, &ident.name, &mut visited));
+
+            if is_recursive {
+                ctx.errors.push(SemaError::new(
+                    SemaErrorKind::RecursiveStruct(ident.name.clone()),
+                    ident.location,
+                ));
+            }
+        }
+    }
+}
+
+fn declare_fn(
+    ctx: &mut SemaCtx,
+    ident: &Ident,
+    params: &[(Ident, Ty)],
+    return_ty: Option<&Ty>,
+) -> Result<(), SemaError> {
+    if RUNTIME_FUNCTIONS.contains(&ident.name.as_str()) {
+        return Err(SemaError::new(
+            SemaErrorKind::ReservedFunction(ident.name.clone()),
+            ident.location,
+        ));
+    }
+
+    if ctx.table.fns.contains_key(&ident.name) {
+        return Err(SemaError::new(
+            SemaErrorKind::DuplicateFunction(ident.name.clone()),
+            ident.location,
+        ));
+    }
+
+    let params = params
+        .iter()
+        .map(|(_, ty)| resolve_value_ty(ctx, ty))
+        .collect::<Result<Vec<Type>, SemaError>>()?;
+    let ret = match return_ty {
+        Some(ty) => check_not_slice(resolve_ty(ctx, ty)?, ty)?,
+        None => Type::Void,
+    };
+
+    ctx.table
+        .fns
+        .insert(ident.name.clone(), FnSig { params, ret });
+
+    Ok(())
+}
+
+fn check_fn(ctx: &mut SemaCtx, ident: &Ident, params: &[(Ident, Ty)], body: &Block) {
+    // Functions with an invalid signature have already been reported when declared.
+    let sig = match ctx.table.fns.get(&ident.name) {
+        Some(sig) => sig.clone(),
+        None => return,
+    };
+
+    ctx.return_ty = Some(sig.ret);
+    ctx.enter_scope();
+
+    for ((ident, _), ty) in params.iter().zip(sig.params) {
+        let var = ctx.infer.known(ty);
+        ctx.declare_var(ident, var, true);
+    }
+
+    check_block(ctx, body);
+
+    // Only `void` functions can reach the end of their body, where they implicitly return.
+    let falls_through = flow::check_body(body, &mut ctx.errors);
+    if falls_through && sig.ret != Type::Void {
+        ctx.errors.push(SemaError::new(
+            SemaErrorKind::MissingReturn(ident.name.clone()),
+            ident.location,
+        ));
+    }
+
+    ctx.exit_scope();
+    ctx.return_ty = None;
+    ctx.unassigned.clear();
+
+    ctx.resolve_fn();
+}
+
+fn check_block(ctx: &mut SemaCtx, block: &Block) {
+    ctx.enter_scope();
+
+    for stmt in &block.stmts {
+        if let Err(err) = check_stmt(ctx, stmt) {
+            ctx.errors.push(err);
+        }
+    }
+
+    ctx.exit_scope();
+}
+
+fn check_stmt(ctx: &mut SemaCtx, stmt: &Stmt) -> Result<(), SemaError> {
+    match &stmt.kind {
+        StmtKind::Decl(decl) => check_decl(ctx, decl),
+        StmtKind::Ret(expr) => {
+            let return_ty = ctx
+                .return_ty
+                .expect("Return statement outside of a function");
+
+            let result = match expr {
+                Some(expr) if return_ty == Type::Void => {
+                    check_expr(ctx, expr)?;
+                    Err(SemaError::new(
+                        SemaErrorKind::UnexpectedReturnValue,
+                        expr.location,
+                    ))
+                }
+                Some(expr) => {
+                    let var = check_expr(ctx, expr)?;
+                    let expected = ctx.infer.known(return_ty);
+                    expect_type(ctx, expected, var, expr.location)
+                }
+                None if return_ty == Type::Void => Ok(()),
+                None => Err(SemaError::new(
+                    SemaErrorKind::MissingReturnValue(return_ty),
+                    stmt.location,
+                )),
+            };
+
+            ctx.unassigned.clear();
+            result
+        }
+        StmtKind::If(condition, then_block, else_stmt) => {
+            if let Err(err) = check_condition(ctx, condition) {
+                ctx.errors.push(err);
+            }
+
+            let before = ctx.unassigned.clone();
+            check_block(ctx, then_block);
+            let after_then = std::mem::replace(&mut ctx.unassigned, before);
+
+            let result = match else_stmt {
+                Some(stmt) => check_stmt(ctx, stmt),
+                None => Ok(()),
+            };
+
+            let after_else = std::mem::take(&mut ctx.unassigned);
+            ctx.merge_unassigned(vec![after_then, after_else]);
+            result
+        }
+        StmtKind::While(label, condition, body) => {
+            if let Err(err) = check_condition(ctx, condition) {
+                ctx.errors.push(err);
+            }
+
+            // The body may not be executed at all.
+            let before = ctx.unassigned.clone();
+            let at_break = check_loop_body(ctx, label.as_ref(), body);
+            ctx.merge_unassigned(vec![before, at_break]);
+            Ok(())
+        }
+        StmtKind::Loop(label, body) => {
+            // A `loop` can only be exited with a `break`.
+            ctx.unassigned = check_loop_body(ctx, label.as_ref(), body);
+            Ok(())
+        }
+        StmtKind::Break(label) => {
+            let result = check_loop_control(ctx, "break", label.as_ref(), stmt.location);
+
+            // The variables unassigned here are unassigned after the loop the `break` exits.
+            let unassigned = std::mem::take(&mut ctx.unassigned);
+            let target = match label {
+                Some(label) => ctx
+                    .loops
+                    .iter_mut()
+                    .rev()
+                    .find(|scope| scope.label.as_ref() == Some(&label.name)),
+                None => ctx.loops.last_mut(),
+            };
+
+            if let Some(target) = target {
+                target.unassigned_at_break.extend(unassigned);
+            }
+
+            result
+        }
+        StmtKind::Continue(label) => {
+            let result = check_loop_control(ctx, "continue", label.as_ref(), stmt.location);
+            ctx.unassigned.clear();
+            result
+        }
+        StmtKind::Block(block) => {
+            check_block(ctx, block);
+            Ok(())
+        }
+        StmtKind::Expr(expr) => check_expr(ctx, expr).map(|_| ()),
+    }
+}
+
+/// Check the body of a loop, returning the variables unassigned on some path breaking out of it.
+fn check_loop_body(ctx: &mut SemaCtx, label: Option<&Ident>, body: &Block) -> HashSet<TypeVar> {
+    ctx.loops.push(LoopScope {
+        label: label.map(|label| label.name.clone()),
+        unassigned_at_break: HashSet::new(),
+    });
+    check_block(ctx, body);
+
+    ctx.loops
+        .pop()
+        .expect("Loop scope already exited")
+        .unassigned_at_break
+}
+
+fn check_loop_control(
+    ctx: &mut SemaCtx,
+    keyword: &str,
+    label: Option<&Ident>,
+    location: Location,
+) -> Result<(), SemaError> {
+    if ctx.loops.is_empty() {
+        return Err(SemaError::new(
+            SemaErrorKind::OutsideOfLoop(keyword.to_owned()),
+            location,
+        ));
+    }
+
+    match label {
+        Some(label)
+            if !ctx
+                .loops
+                .iter()
+                .any(|scope| scope.label.as_ref() == Some(&label.name)) =>
+        {
+            Err(SemaError::new(
+                SemaErrorKind::UnknownLabel(label.name.clone()),
+                label.location,
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn check_condition(ctx: &mut SemaCtx, condition: &Expr) -> Result<(), SemaError> {
+    let var = check_expr(ctx, condition)?;
+    let expected = ctx.infer.known(Type::Bool);
+    expect_type(ctx, expected, var, condition.location)
+}
+
+fn check_decl(ctx: &mut SemaCtx, decl: &Decl) -> Result<(), SemaError> {
+    match &decl.kind {
+        DeclKind::Var(ident, ty, init) => {
+            // Variables without annotation get their type inferred from the initializer or
+            // from the way they are used in the rest of the function body.
+            let var = match ty {
+                Some(ty) => match resolve_value_ty(ctx, ty) {
+                    Ok(ty) => ctx.infer.known(ty),
+                    Err(err) => {
+                        let var = ctx.infer.fresh();
+                        ctx.declare_var(ident, var, false);
+                        return Err(err);
+                    }
+                },
+                None => ctx.infer.fresh(),
+            };
+
+            let result = match init {
+                Some(expr) => check_expr(ctx, expr).and_then(|init_var| {
+                    check_not_void(ctx, init_var, expr.location)?;
+                    expect_type(ctx, var, init_var, expr.location)
+                }),
+                None => {
+                    ctx.unassigned.insert(var);
+                    Ok(())
+                }
+            };
+
+            ctx.declare_var(ident, var, result.is_ok());
+            result
+        }
+    }
+}
+
+/// Check that a value stored in a variable is not the result of a call to a `void` function,
+/// which doesn't produce a value to store.
+fn check_not_void(ctx: &SemaCtx, var: TypeVar, location: Location) -> Result<(), SemaError> {
+    if ctx.infer.resolve(var) == Some(Type::Void) {
+        Err(SemaError::new(SemaErrorKind::InvalidVoidType, location))
+    } else {
+        Ok(())
+    }
+}
+
+fn check_expr(ctx: &mut SemaCtx, expr: &Expr) -> Result<TypeVar, SemaError> {
+    let var = match &expr.kind {
+        ExprKind::Assign(target, value) => {
+            let value_var = check_expr(ctx, value)?;
+            let target_var = match &target.kind {
+                // Assigning a variable doesn't read it, so it can still be unassigned.
+                ExprKind::Ident(ident) => {
+                    let var = ctx.lookup_var(ident)?;
+                    ctx.unassigned.remove(&var);
+                    ctx.exprs.push((target.location, var));
+                    var
+                }
+                _ => check_assign_target(ctx, target)?,
+            };
+
+            if let Err(err) = check_not_void(ctx, value_var, value.location) {
+                ctx.invalidate_var(target_var);
+                return Err(err);
+            }
+
+            expect_type(ctx, target_var, value_var, value.location)?;
+            target_var
+        }
+        ExprKind::AssignOp(op, target, value) => {
+            let value_var = check_expr(ctx, value)?;
+            let target_var = check_assign_target(ctx, target)?;
+
+            check_assign_op(ctx, op, target_var, value_var, expr.location)?
+        }
+        ExprKind::Logical(op, left, right) => {
+            let lhs = check_expr(ctx, left)?;
+
+            // The right operand may not be evaluated, so its assignments don't count.
+            let before = ctx.unassigned.clone();
+            let rhs = check_expr(ctx, right)?;
+            ctx.unassigned = before;
+
+            check_logical(ctx, op, lhs, rhs, expr.location)?
+        }
+        ExprKind::Binary(op, left, right) => {
+            let lhs = check_expr(ctx, left)?;
+            let rhs = check_expr(ctx, right)?;
+
+            check_binary(ctx, op, lhs, rhs, expr.location)?
+        }
+        ExprKind::Unary(op, operand) => {
+            let var = check_expr(ctx, operand)?;
+
+            // Negated literals are checked against the lower bound of their type.
+            if let UnaryOp::Minus = op {
+                if let Some(literal) = ctx
+                    .int_literals
+                    .last_mut()
+                    .filter(|literal| literal.location == operand.location)
+                {
+                    literal.value = -literal.value;
+                    literal.location = expr.location;
+                }
+            }
+
+            check_unary(ctx, op, var, expr.location)?
+        }
+        ExprKind::If(condition, then_expr, else_expr) => {
+            check_condition(ctx, condition)?;
+
+            // Both branches produce a value of the same type.
+            let before = ctx.unassigned.clone();
+            let then_var = check_expr(ctx, then_expr)?;
+            let after_then = std::mem::replace(&mut ctx.unassigned, before);
+            let else_var = check_expr(ctx, else_expr)?;
+            let after_else = std::mem::take(&mut ctx.unassigned);
+            ctx.merge_unassigned(vec![after_then, after_else]);
+
+            expect_type(ctx, then_var, else_var, else_expr.location)?;
+
+            then_var
+        }
+        ExprKind::Ident(ident) => {
+            let var = ctx.lookup_var(ident)?;
+
+            // The variable is only reported once, and considered assigned afterwards.
+            if ctx.unassigned.remove(&var) {
+                ctx.invalidate_var(var);
+                return Err(SemaError::new(
+                    SemaErrorKind::UnassignedVariable(ident.name.clone()),
+                    ident.location,
+                ));
+            }
+
+            var
+        }
+        ExprKind::Call(callee, args) => check_call(ctx, callee, args, expr.location)?,
+        ExprKind::Field(base, field) => {
+            let base_var = check_expr(ctx, base)?;
+
+            check_field(ctx, base_var, field, base.location)?
+        }
+        ExprKind::Index(base, index) => {
+            let base_var = check_expr(ctx, base)?;
+            let index_var = check_expr(ctx, index)?;
+
+            check_index(ctx, base_var, index_var, base.location, index.location)?
+        }
+        ExprKind::Slice(base, start, end) => {
+            let base_var = check_expr(ctx, base)?;
+
+            let mut bounds = Vec::new();
+            for bound in [start, end].into_iter().flatten() {
+                bounds.push((check_expr(ctx, bound)?, bound.location));
+            }
+
+            check_slice(ctx, base_var, &bounds, base.location)?
+        }
+        ExprKind::Struct(ident, fields) => check_struct_literal(ctx, ident, fields)?,
+        ExprKind::Array(elements) => check_array_literal(ctx, elements)?,
+        ExprKind::Lit(lit) => match lit.kind {
+            LitKind::Int(value) => {
+                let var = ctx.infer.int_literal();
+                ctx.int_literals.push(IntLiteral {
+                    var,
+                    value: value.into(),
+                    location: expr.location,
+                });
+                var
+            }
+            LitKind::Float(_) => ctx.infer.float_literal(),
+            LitKind::Bool(_) => ctx.infer.known(Type::Bool),
+            LitKind::Str(_) => ctx.infer.known(Type::Str),
+            LitKind::Char(_) => ctx.infer.known(Type::UInt(UIntTy::U8)),
+        },
+    };
+
+    ctx.exprs.push((expr.location, var));
+    Ok(var)
+}
+
+/// Check the target of an assignment. The bytes of a string can't be assigned, as strings are
+/// immutable.
+fn check_assign_target(ctx: &mut SemaCtx, target: &Expr) -> Result<TypeVar, SemaError> {
+    let (base, index) = match &target.kind {
+        ExprKind::Index(base, index) => (base, index),
+        _ => return check_expr(ctx, target),
+    };
+
+    let base_var = check_expr(ctx, base)?;
+    if ctx.infer.resolve(base_var) == Some(Type::Str) {
+        return Err(SemaError::new(
+            SemaErrorKind::ImmutableString,
+            target.location,
+        ));
+    }
+
+    let index_var = check_expr(ctx, index)?;
+    let var = check_index(ctx, base_var, index_var, base.location, index.location)?;
+
+    ctx.exprs.push((target.location, var));
+    Ok(var)
+}
+
+fn check_logical(
+    ctx: &mut SemaCtx,
+    op: &LogicalOp,
+    lhs: TypeVar,
+    rhs: TypeVar,
+    location: Location,
+) -> Result<TypeVar, SemaError> {
+    unify_operands(ctx, op.to_string(), lhs, rhs, location)?;
+
+    ctx.check_operand(OperandCheck {
+        var: lhs,
+        is_valid: |ty| *ty == Type::Bool,
+        kind: OperandKind::Binary(op.to_string()),
+        location,
+    })?;
+
+    Ok(lhs)
+}
+
+fn check_binary(
+    ctx: &mut SemaCtx,
+    op: &BinaryOp,
+    lhs: TypeVar,
+    rhs: TypeVar,
+    location: Location,
+) -> Result<TypeVar, SemaError> {
+    unify_operands(ctx, op.to_string(), lhs, rhs, location)?;
+
+    let is_valid: fn(&Type) -> bool = match op {
+        BinaryOp::Equal | BinaryOp::NotEqual => {
+            |ty| matches!(ty, Type::Bool | Type::Str) || ty.is_numeric()
+        }
+        _ => Type::is_numeric,
+    };
+
+    ctx.check_operand(OperandCheck {
+        var: lhs,
+        is_valid,
+        kind: OperandKind::Binary(op.to_string()),
+        location,
+    })?;
+
+    let var = match op {
+        BinaryOp::Add | BinaryOp::Subtract | BinaryOp::Multiply | BinaryOp::Divide => lhs,
+        BinaryOp::Equal
+        | BinaryOp::NotEqual
+        | BinaryOp::Greater
+        | BinaryOp::GreaterEqual
+        | BinaryOp::Less
+        | BinaryOp::LessEqual => ctx.infer.known(Type::Bool),
+    };
+
+    Ok(var)
+}
+
+fn check_assign_op(
+    ctx: &mut SemaCtx,
+    op: &BinaryOp,
+    target: TypeVar,
+    value: TypeVar,
+    location: Location,
+) -> Result<TypeVar, SemaError> {
+    let op = format!("{}=", op);
+
+    unify_operands(ctx, op.clone(), target, value, location)?;
+
+    ctx.check_operand(OperandCheck {
+        var: target,
+        is_valid: Type::is_numeric,
+        kind: OperandKind::Binary(op),
+        location,
+    })?;
+
+    Ok(target)
+}
+
+fn check_unary(
+    ctx: &mut SemaCtx,
+    op: &UnaryOp,
+    var: TypeVar,
+    location: Location,
+) -> Result<TypeVar, SemaError> {
+    let is_valid: fn(&Type) -> bool = match op {
+        UnaryOp::Not => |ty| *ty == Type::Bool || ty.is_integer(),
+        UnaryOp::Minus => |ty| ty.is_signed() || ty.is_float(),
+    };
+
+    ctx.check_operand(OperandCheck {
+        var,
+        is_valid,
+        kind: OperandKind::Unary(op.to_string()),
+        location,
+    })?;
+
+    Ok(var)
+}
+
+fn check_call(
+    ctx: &mut SemaCtx,
+    callee: &Expr,
+    args: &[Box<Expr>],
+    location: Location,
+) -> Result<TypeVar, SemaError> {
+    let ident = match &callee.kind {
+        ExprKind::Ident(ident) => ident,
+        _ => {
+            return Err(SemaError::new(
+                SemaErrorKind::InvalidCallee,
+                callee.location,
+            ))
+        }
+    };
+
+    let sig = match ctx.table.fns.get(&ident.name) {
+        Some(sig) => sig.clone(),
+        // Intrinsics can be shadowed by a function with the same name.
+        None if ident.name == "len" => return check_len(ctx, args, location),
+        None if ident.name == "print" => return check_print(ctx, args, location),
+        None => {
+            return Err(SemaError::new(
+                SemaErrorKind::UnknownFunction(ident.name.clone()),
+                ident.location,
+            ))
+        }
+    };
+
+    if sig.params.len() != args.len() {
+        return Err(SemaError::new(
+            SemaErrorKind::InvalidArgumentCount {
+                name: ident.name.clone(),
+                expected: sig.params.len(),
+                actual: args.len(),
+            },
+            location,
+        ));
+    }
+
+    for (arg, param_ty) in args.iter().zip(sig.params) {
+        let arg_var = check_expr(ctx, arg)?;
+        let param_var = ctx.infer.known(param_ty);
+        expect_type(ctx, param_var, arg_var, arg.location)?;
+    }
+
+    Ok(ctx.infer.known(sig.ret))
+}
+
+fn check_field(
+    ctx: &mut SemaCtx,
+    base: TypeVar,
+    field: &Ident,
+    base_location: Location,
+) -> Result<TypeVar, SemaError> {
+    // The struct of the field needs to be known to resolve the type of the field.
+    let ty = ctx
+        .infer
+        .resolve(base)
+        .ok_or_else(|| SemaError::new(SemaErrorKind::UnknownExprType, base_location))?;
+
+    let name = match ty {
+        Type::Struct(name) => name,
+        _ => {
+            return Err(SemaError::new(
+                SemaErrorKind::InvalidFieldAccess(ty),
+                field.location,
+            ))
+        }
+    };
+
+    match ctx.table.structs[name].field(&field.name) {
+        Some((_, field_ty)) => Ok(ctx.infer.known(field_ty)),
+        None => Err(SemaError::new(
+            SemaErrorKind::UnknownField {
+                ty,
+                field: field.name.clone(),
+            },
+            field.location,
+        )),
+    }
+}
+
+/// Returns the variable of the element type of an indexed array, slice or string. Strings are
+/// indexed by bytes, and can only be used when `allow_str` is set.
+fn check_array_element(
+    ctx: &mut SemaCtx,
+    base: TypeVar,
+    base_location: Location,
+    allow_str: bool,
+    error: fn(Type) -> SemaErrorKind,
+) -> Result<TypeVar, SemaError> {
+    if let Some((element, _)) = ctx.infer.as_array(base) {
+        return Ok(element);
+    }
+
+    if let Some(element) = ctx.infer.as_slice(base) {
+        return Ok(element);
+    }
+
+    if allow_str && ctx.infer.resolve(base) == Some(Type::Str) {
+        return Ok(ctx.infer.known(Type::UInt(UIntTy::U8)));
+    }
+
+    // The array needs to be known to resolve the type of its elements.
+    if ctx.infer.is_unbound(base) {
+        Err(SemaError::new(
+            SemaErrorKind::UnknownExprType,
+            base_location,
+        ))
+    } else {
+        Err(SemaError::new(
+            error(ctx.infer.describe(base)),
+            base_location,
+        ))
+    }
+}
+
+fn check_index(
+    ctx: &mut SemaCtx,
+    base: TypeVar,
+    index: TypeVar,
+    base_location: Location,
+    index_location: Location,
+) -> Result<TypeVar, SemaError> {
+    let element = check_array_element(ctx, base, base_location, true, SemaErrorKind::InvalidIndex)?;
+
+    ctx.check_operand(OperandCheck {
+        var: index,
+        is_valid: Type::is_integer,
+        kind: OperandKind::Index,
+        location: index_location,
+    })?;
+
+    Ok(element)
+}
+
+/// Check a slice of an array or of another slice, returning the variable of the slice type.
+/// The bounds are indexes of the sliced elements.
+fn check_slice(
+    ctx: &mut SemaCtx,
+    base: TypeVar,
+    bounds: &[(TypeVar, Location)],
+    base_location: Location,
+) -> Result<TypeVar, SemaError> {
+    let element =
+        check_array_element(ctx, base, base_location, false, SemaErrorKind::InvalidSlice)?;
+
+    for (bound, location) in bounds {
+        ctx.check_operand(OperandCheck {
+            var: *bound,
+            is_valid: Type::is_integer,
+            kind: OperandKind::Index,
+            location: *location,
+        })?;
+    }
+
+    Ok(ctx.infer.slice(element))
+}
+
+/// Returns the argument of an intrinsic taking a single argument.
+fn intrinsic_arg<'a>(
+    name: &str,
+    args: &'a [Box<Expr>],
+    location: Location,
+) -> Result<&'a Expr, SemaError> {
+    match args {
+        [arg] => Ok(arg),
+        _ => Err(SemaError::new(
+            SemaErrorKind::InvalidArgumentCount {
+                name: name.to_owned(),
+                expected: 1,
+                actual: args.len(),
+            },
+            location,
+        )),
+    }
+}
+
+/// Check a call to the `len` intrinsic, returning the length of an array or a slice, or the
+/// number of bytes of a string.
+fn check_len(
+    ctx: &mut SemaCtx,
+    args: &[Box<Expr>],
+    location: Location,
+) -> Result<TypeVar, SemaError> {
+    let arg = intrinsic_arg("len", args, location)?;
+
+    let arg_var = check_expr(ctx, arg)?;
+    check_array_element(
+        ctx,
+        arg_var,
+        arg.location,
+        true,
+        SemaErrorKind::InvalidLenArgument,
+    )?;
+
+    Ok(ctx.infer.known(Type::UInt(UIntTy::U64)))
+}
+
+/// Check a call to the `print` intrinsic, writing a string to the standard output.
+fn check_print(
+    ctx: &mut SemaCtx,
+    args: &[Box<Expr>],
+    location: Location,
+) -> Result<TypeVar, SemaError> {
+    let arg = intrinsic_arg("print", args, location)?;
+
+    let arg_var = check_expr(ctx, arg)?;
+    let expected = ctx.infer.known(Type::Str);
+    expect_type(ctx, expected, arg_var, arg.location)?;
+
+    Ok(ctx.infer.known(Type::Void))
+}
+
+fn check_array_literal(ctx: &mut SemaCtx, elements: &[Box<Expr>]) -> Result<TypeVar, SemaError> {
+    let (first, rest) = elements
+        .split_first()
+        .expect("Array literal without elements");
+
+    // The elements get the type of the first one, which needs to be known at this point.
+    let element = check_expr(ctx, first)?;
+    match ctx.infer.resolve(element) {
+        Some(Type::Void) => {
+            return Err(SemaError::new(
+                SemaErrorKind::InvalidVoidType,
+                first.location,
+            ))
+        }
+        None if ctx.infer.is_unbound(element) => {
+            return Err(SemaError::new(
+                SemaErrorKind::UnknownExprType,
+                first.location,
+            ))
+        }
+        _ => {}
+    }
+
+    for expr in rest {
+        let var = check_expr(ctx, expr)?;
+        expect_type(ctx, element, var, expr.location)?;
+    }
+
+    Ok(ctx.infer.array(element, elements.len() as u64))
+}
+
+fn check_struct_literal(
+    ctx: &mut SemaCtx,
+    ident: &Ident,
+    fields: &[(Ident, Box<Expr>)],
+) -> Result<TypeVar, SemaError> {
+    let def = match ctx.table.structs.get(&ident.name) {
+        Some(def) => def.clone(),
+        None => {
+            return Err(SemaError::new(
+                SemaErrorKind::UnknownType(ident.name.clone()),
+                ident.location,
+            ))
+        }
+    };
+    let ty = Type::struct_named(&def.name);
+
+    let mut initialized: Vec<&str> = Vec::new();
+    for (field, value) in fields {
+        let value_var = check_expr(ctx, value)?;
+
+        let field_ty = match def.field(&field.name) {
+            Some((_, field_ty)) => field_ty,
+            None => {
+                return Err(SemaError::new(
+                    SemaErrorKind::UnknownField {
+                        ty,
+                        field: field.name.clone(),
+                    },
+                    field.location,
+                ))
+            }
+        };
+
+        if initialized.contains(&field.name.as_str()) {
+            return Err(SemaError::new(
+                SemaErrorKind::DuplicateField(field.name.clone()),
+                field.location,
+            ));
+        }
+        initialized.push(&field.name);
+
+        let expected = ctx.infer.known(field_ty);
+        expect_type(ctx, expected, value_var, value.location)?;
+    }
+
+    let missing = def
+        .fields
+        .iter()
+        .filter(|(name, _)| !initialized.contains(&name.as_str()))
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<_>>();
+
+    if !missing.is_empty() {
+        return Err(SemaError::new(
+            SemaErrorKind::MissingFields {
+                ty,
+                fields: missing,
+            },
+            ident.location,
+        ));
+    }
+
+    Ok(ctx.infer.known(ty))
+}
+
+fn unify_operands(
+    ctx: &mut SemaCtx,
+    op: String,
+    lhs: TypeVar,
+    rhs: TypeVar,
+    location: Location,
+) -> Result<(), SemaError> {
+    ctx.infer.unify(lhs, rhs).map_err(|(lhs, rhs)| {
+        SemaError::new(
+            SemaErrorKind::InvalidBinaryOperands { op, lhs, rhs },
+            location,
+        )
+    })
+}
+
+fn expect_type(
+    ctx: &mut SemaCtx,
+    expected: TypeVar,
+    actual: TypeVar,
+    location: Location,
+) -> Result<(), SemaError> {
+    ctx.infer
+        .unify(expected, actual)
+        .map_err(|(expected, actual)| {
+            SemaError::new(
+                SemaErrorKind::MismatchedTypes { expected, actual },
+                location,
+            )
+        })
+}
+
+fn resolve_ty(ctx: &SemaCtx, ty: &Ty) -> Result<Type, SemaError> {
+    let name = match &ty.kind {
+        TyKind::Named(name) => name,
+        TyKind::Array(element, len) => {
+            let element = resolve_value_ty(ctx, element)?;
+            return Ok(Type::array_of(element, *len));
+        }
+        TyKind::Slice(element) => {
+            let element = resolve_value_ty(ctx, element)?;
+            return Ok(Type::slice_of(element));
+        }
+    };
+
+    if let Some(builtin) = Type::from_name(name) {
+        return Ok(builtin);
+    }
+
+    if ctx.table.structs.contains_key(name) {
+        Ok(Type::struct_named(name))
+    } else {
+        Err(SemaError::new(
+            SemaErrorKind::UnknownType(name.clone()),
+            ty.location,
+        ))
+    }
+}
+
+/// Check that a return or field type doesn't hold a slice, which could outlive its array once
+/// the function returns.
+fn check_not_slice(resolved: Type, ty: &Ty) -> Result<Type, SemaError> {
+    if resolved.contains_slice() {
+        Err(SemaError::new(
+            SemaErrorKind::EscapingSlice(resolved),
+            ty.location,
+        ))
+    } else {
+        Ok(resolved)
+    }
+}
+
+/// Resolve the type of a variable, a parameter or a field, which can't be `void`.
+fn resolve_value_ty(ctx: &SemaCtx, ty: &Ty) -> Result<Type, SemaError> {
+    match resolve_ty(ctx, ty)? {
+        Type::Void => Err(SemaError::new(SemaErrorKind::InvalidVoidType, ty.location)),
+        ty => Ok(ty),
+    }
+}
@@ -0,0 +1,222 @@
+use std::{collections::HashSet, fmt, sync::Mutex};
+
+use crate::ast::location::Location;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IntTy {
+    I8,
+    I16,
+    I32,
+    I64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UIntTy {
+    U8,
+    U16,
+    U32,
+    U64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FloatTy {
+    F32,
+    F64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Type {
+    Void,
+    Bool,
+    Int(IntTy),
+    UInt(UIntTy),
+    Float(FloatTy),
+    /// Immutable UTF-8 string, made of a pointer to its bytes and its length in bytes.
+    Str,
+    /// Struct type, identified by the name of its declaration.
+    Struct(&'static str),
+    /// Fixed-size array, holding the type of its elements and its length.
+    Array(&'static (Type, u64)),
+    /// View into the elements of an array, made of a pointer to its first element and its
+    /// length. The elements are not owned by the slice, which must not outlive the array: slices
+    /// can't be returned from functions or stored in structs.
+    Slice(&'static Type),
+}
+
+impl Type {
+    /// Type of an integer literal when the context doesn't constrain it.
+    pub const DEFAULT_INT: Type = Type::Int(IntTy::I32);
+    /// Type of a float literal when the context doesn't constrain it.
+    pub const DEFAULT_FLOAT: Type = Type::Float(FloatTy::F64);
+
+    pub fn from_name(name: &str) -> Option<Type> {
+        match name {
+            "void" => Some(Type::Void),
+            "bool" => Some(Type::Bool),
+            "i8" => Some(Type::Int(IntTy::I8)),
+            "i16" => Some(Type::Int(IntTy::I16)),
+            "i32" | "int" => Some(Type::Int(IntTy::I32)),
+            "i64" => Some(Type::Int(IntTy::I64)),
+            "u8" => Some(Type::UInt(UIntTy::U8)),
+            "u16" => Some(Type::UInt(UIntTy::U16)),
+            "u32" => Some(Type::UInt(UIntTy::U32)),
+            "u64" => Some(Type::UInt(UIntTy::U64)),
+            "f32" => Some(Type::Float(FloatTy::F32)),
+            "f64" | "float" => Some(Type::Float(FloatTy::F64)),
+            "str" => Some(Type::Str),
+            _ => None,
+        }
+    }
+
+    /// Returns the type of the struct declared with `name`. Struct names are interned, so that
+    /// struct types can be copied and compared like the other types.
+    pub fn struct_named(name: &str) -> Type {
+        lazy_static! {
+            static ref NAMES: Mutex<HashSet<&'static str>> = Mutex::new(HashSet::new());
+        }
+
+        let mut names = NAMES.lock().unwrap();
+        let name = match names.get(name) {
+            Some(name) => *name,
+            None => {
+                let name: &'static str = Box::leak(name.to_owned().into_boxed_str());
+                names.insert(name);
+                name
+            }
+        };
+
+        Type::Struct(name)
+    }
+
+    /// Returns the type of the arrays of `len` elements of type `element`. Array types are
+    /// interned like the struct names.
+    pub fn array_of(element: Type, len: u64) -> Type {
+        lazy_static! {
+            static ref ARRAYS: Mutex<HashSet<&'static (Type, u64)>> = Mutex::new(HashSet::new());
+        }
+
+        let mut arrays = ARRAYS.lock().unwrap();
+        let array = match arrays.get(&(element, len)) {
+            Some(array) => *array,
+            None => {
+                let array: &'static (Type, u64) = Box::leak(Box::new((element, len)));
+                arrays.insert(array);
+                array
+            }
+        };
+
+        Type::Array(array)
+    }
+
+    /// Returns the type of the slices of elements of type `element`. Slice types are interned
+    /// like the array types.
+    pub fn slice_of(element: Type) -> Type {
+        lazy_static! {
+            static ref SLICES: Mutex<HashSet<&'static Type>> = Mutex::new(HashSet::new());
+        }
+
+        let mut slices = SLICES.lock().unwrap();
+        let slice = match slices.get(&element) {
+            Some(slice) => *slice,
+            None => {
+                let slice: &'static Type = Box::leak(Box::new(element));
+                slices.insert(slice);
+                slice
+            }
+        };
+
+        Type::Slice(slice)
+    }
+
+    pub fn is_integer(&self) -> bool {
+        matches!(self, Type::Int(_) | Type::UInt(_))
+    }
+
+    pub fn is_signed(&self) -> bool {
+        matches!(self, Type::Int(_))
+    }
+
+    pub fn is_float(&self) -> bool {
+        matches!(self, Type::Float(_))
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        self.is_integer() || self.is_float()
+    }
+
+    /// Returns whether values of this type hold a slice, directly or through their elements.
+    /// Structs can't have slice fields, so they never hold one.
+    pub fn contains_slice(&self) -> bool {
+        match self {
+            Type::Slice(_) => true,
+            Type::Array((element, _)) => element.contains_slice(),
+            _ => false,
+        }
+    }
+
+    /// Returns whether `value` is between the bounds of an integer type.
+    pub fn contains_int(&self, value: i128) -> bool {
+        let (min, max) = match self {
+            Type::Int(IntTy::I8) => (i8::MIN.into(), i8::MAX.into()),
+            Type::Int(IntTy::I16) => (i16::MIN.into(), i16::MAX.into()),
+            Type::Int(IntTy::I32) => (i32::MIN.into(), i32::MAX.into()),
+            Type::Int(IntTy::I64) => (i64::MIN.into(), i64::MAX.into()),
+            Type::UInt(UIntTy::U8) => (0, u8::MAX.into()),
+            Type::UInt(UIntTy::U16) => (0, u16::MAX.into()),
+            Type::UInt(UIntTy::U32) => (0, u32::MAX.into()),
+            Type::UInt(UIntTy::U64) => (0, u64::MAX.into()),
+            _ => unreachable!("Type {} isn't an integer type", self),
+        };
+
+        (min..=max).contains(&value)
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Type::Void => "void",
+            Type::Bool => "bool",
+            Type::Int(IntTy::I8) => "i8",
+            Type::Int(IntTy::I16) => "i16",
+            Type::Int(IntTy::I32) => "i32",
+            Type::Int(IntTy::I64) => "i64",
+            Type::UInt(UIntTy::U8) => "u8",
+            Type::UInt(UIntTy::U16) => "u16",
+            Type::UInt(UIntTy::U32) => "u32",
+            Type::UInt(UIntTy::U64) => "u64",
+            Type::Float(FloatTy::F32) => "f32",
+            Type::Float(FloatTy::F64) => "f64",
+            Type::Str => "str",
+            Type::Struct(name) => name,
+            Type::Array((element, len)) => return write!(f, "[{}; {}]", element, len),
+            Type::Slice(element) => return write!(f, "[{}]", element),
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FnSig {
+    pub params: Vec<Type>,
+    pub ret: Type,
+}
+
+#[derive(Debug, Clone)]
+pub struct StructDef {
+    pub name: String,
+    /// Fields in declaration order, which is also their order in memory.
+    pub fields: Vec<(String, Type)>,
+    pub location: Location,
+}
+
+impl StructDef {
+    /// Returns the index and the type of a field.
+    pub fn field(&self, name: &str) -> Option<(usize, Type)> {
+        self.fields
+            .iter()
+            .position(|(field_name, _)| field_name == name)
+            .map(|index| (index, self.fields[index].1))
+    }
+}
@@ -0,0 +1,28 @@
+use inkwell::context::Context;
+use test_generator::test_resources;
+use turbo_bear::sema;
+
+mod common;
+
+#[test_resources("tests/fixtures/sema/*.tb")]
+fn codegen(path: &str) {
+    let (source_map, file_id) = common::load(path);
+    let file = source_map.file(file_id);
+    let program = common::parse(file);
+
+    // Only the programs accepted by the semantic analysis are compiled.
+    let types = match sema::check(&program) {
+        (Some(types), _) => types,
+        (None, _) => return,
+    };
+
+    let context = Context::create();
+    let module = common::compile(&context, file, &program, &types);
+
+    if let Err(err) = module.verify() {
+        panic!("invalid module for {}:\n{}", path, err);
+    }
+
+    let ir = module.print_to_string().to_string();
+    insta::assert_snapshot!(ir);
+}
@@ -0,0 +1,56 @@
+//! Helpers shared by the integration tests, running the compiler stages on the fixtures.
+#![allow(dead_code)]
+
+use std::fs;
+
+use inkwell::{context::Context, module::Module};
+use turbo_bear::{
+    ast::{
+        ast::Program,
+        location::{FileId, SourceFile, SourceMap},
+    },
+    codegen::{self, CodegenOptions},
+    parser,
+    sema::{self, TypeTable},
+};
+
+/// Load the fixture at `path` in a new source map.
+pub fn load(path: &str) -> (SourceMap, FileId) {
+    let input = fs::read_to_string(path).unwrap();
+    let mut source_map = SourceMap::new();
+    let file_id = source_map.add_file(path.to_owned(), input);
+    (source_map, file_id)
+}
+
+/// Parse a file expected to be free of syntax errors.
+pub fn parse(file: &SourceFile) -> Program {
+    let (program, errors) = parser::parse(file);
+    assert!(errors.is_empty(), "{:?}", errors);
+    program.unwrap()
+}
+
+/// Check a program expected to be accepted by the semantic analysis.
+pub fn check(program: &Program) -> TypeTable {
+    let (types, errors) = sema::check(program);
+    assert!(errors.is_empty(), "{:?}", errors);
+    types.unwrap()
+}
+
+/// Compile a checked program to a module without target, with the default options.
+pub fn compile<'ctx>(
+    context: &'ctx Context,
+    file: &'ctx SourceFile,
+    program: &'ctx Program,
+    types: &'ctx TypeTable,
+) -> Module<'ctx> {
+    codegen::compile(
+        context,
+        "main",
+        file,
+        program,
+        types,
+        None,
+        CodegenOptions::default(),
+    )
+    .unwrap()
+}
@@ -0,0 +1,18 @@
+use test_generator::test_resources;
+use turbo_bear::{diagnostics::Diagnostic, parser};
+
+mod common;
+
+#[test_resources("tests/fixtures/invalid_*.tb")]
+fn diagnostics(path: &str) {
+    let (source_map, file_id) = common::load(path);
+    let file = source_map.file(file_id);
+    let (_, errors) = parser::parse(file);
+
+    let output = errors
+        .iter()
+        .map(|err| Diagnostic::from(err).render(file, false))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    insta::assert_snapshot!(output);
+}
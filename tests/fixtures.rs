@@ -1,11 +1,12 @@
-use std::fs;
-
 use test_generator::test_resources;
 use turbo_bear::parser;
 
+mod common;
+
 #[test_resources("tests/fixtures/*.tb")]
 fn fixture(path: &str) {
-    let input = fs::read_to_string(path).unwrap();
-    let program = parser::parse(&input);
+    let (source_map, file_id) = common::load(path);
+    let file = source_map.file(file_id);
+    let program = parser::parse(file);
     insta::assert_debug_snapshot!(program);
 }
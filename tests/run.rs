@@ -0,0 +1,44 @@
+use std::process::Command;
+
+use inkwell::{context::Context, OptimizationLevel};
+use test_generator::test_resources;
+use turbo_bear::codegen::jit;
+
+mod common;
+
+#[test_resources("tests/fixtures/run/*.tb")]
+fn run(path: &str) {
+    let (source_map, file_id) = common::load(path);
+    let file = source_map.file(file_id);
+    let program = common::parse(file);
+    let types = common::check(&program);
+
+    let context = Context::create();
+    let module = common::compile(&context, file, &program, &types);
+
+    // The snapshot holds the value returned by `main`, used as the exit code of the program.
+    let result = jit::run_main(&module, OptimizationLevel::None);
+    insta::assert_debug_snapshot!(result);
+}
+
+#[test]
+fn index_out_of_bounds() {
+    // The program aborts, so it is run in its own process.
+    let path = "tests/fixtures/run/abort/index_out_of_bounds.tb";
+    let output = Command::new(env!("CARGO_BIN_EXE_turbo-bear"))
+        .args(["run", path])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+
+        const SIGABRT: i32 = 6;
+        assert_eq!(output.status.signal(), Some(SIGABRT));
+    }
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(stderr, format!("{}:2:12: index out of bounds\n", path));
+}
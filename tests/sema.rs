@@ -0,0 +1,28 @@
+use test_generator::test_resources;
+use turbo_bear::sema;
+
+mod common;
+
+#[test_resources("tests/fixtures/sema/*.tb")]
+fn sema(path: &str) {
+    let (source_map, file_id) = common::load(path);
+    let file = source_map.file(file_id);
+    let (types, errors) = sema::check(&common::parse(file));
+    let src = file.src();
+
+    // The variables are listed in source order, with the type resolved for their declaration.
+    let vars = types.map(|types| {
+        let mut vars = types
+            .vars()
+            .map(|(location, ty)| (location.start, location.end, ty))
+            .collect::<Vec<_>>();
+        vars.sort_by_key(|(start, end, _)| (*start, *end));
+
+        vars.into_iter()
+            .map(|(start, end, ty)| format!("{}..{} {}: {}", start, end, &src[start..end], ty))
+            .collect::<Vec<_>>()
+    });
+
+    let result = (vars, errors);
+    insta::assert_debug_snapshot!(result);
+}
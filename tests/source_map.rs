@@ -0,0 +1,44 @@
+use turbo_bear::ast::location::{LineCol, Location, SourceMap};
+
+#[test]
+fn line_col() {
+    let mut source_map = SourceMap::new();
+    let file_id = source_map.add_file("main.tb".to_owned(), "fn a\nlet b;\r\n\nc".to_owned());
+    let file = source_map.file(file_id);
+
+    assert_eq!(file.line_count(), 4);
+    assert_eq!(file.line_col(0), LineCol { line: 0, col: 0 });
+    assert_eq!(file.line_col(4), LineCol { line: 0, col: 4 });
+    assert_eq!(file.line_col(5), LineCol { line: 1, col: 0 });
+    assert_eq!(file.line_col(9), LineCol { line: 1, col: 4 });
+    assert_eq!(file.line_col(13), LineCol { line: 2, col: 0 });
+    assert_eq!(file.line_col(14), LineCol { line: 3, col: 0 });
+    assert_eq!(file.line_range(1), (5, 11));
+}
+
+#[test]
+fn line_col_utf16() {
+    let mut source_map = SourceMap::new();
+    let file_id = source_map.add_file("main.tb".to_owned(), "a\n\u{e9}\u{1f43b} b".to_owned());
+    let file = source_map.file(file_id);
+
+    // 'é' is encoded with 2 bytes in UTF-8 and a single UTF-16 code unit, while the emoji is
+    // encoded with 4 bytes in UTF-8 and a surrogate pair in UTF-16.
+    assert_eq!(file.line_col(9), LineCol { line: 1, col: 7 });
+    assert_eq!(file.line_col_utf16(9), LineCol { line: 1, col: 4 });
+}
+
+#[test]
+fn multiple_files() {
+    let mut source_map = SourceMap::new();
+    let first = source_map.add_file("first.tb".to_owned(), "a\nb".to_owned());
+    let second = source_map.add_file("second.tb".to_owned(), "ab\ncd\nef".to_owned());
+
+    assert_ne!(first, second);
+    assert_eq!(source_map.file(first).name(), "first.tb");
+    assert_eq!(source_map.file(second).name(), "second.tb");
+
+    let (start, end) = source_map.line_col(Location::new(second, 4, 7));
+    assert_eq!(start, LineCol { line: 1, col: 1 });
+    assert_eq!(end, LineCol { line: 2, col: 1 });
+}